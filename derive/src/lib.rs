@@ -0,0 +1,340 @@
+//! `#[derive(Placeholder)]` for the `empty-option` crate. Use it through `empty-option`'s
+//! `derive` feature rather than depending on this crate directly.
+//!
+//! The sentinel can be given explicitly with a `#[placeholder(...)]` attribute holding any
+//! expression, which is the only option for enums. Structs without the attribute are built from
+//! the placeholders of each of their fields, and a generic struct's impl requires each field's type
+//! to be a `Placeholder` in turn. An enum without the attribute is rejected:
+//!
+//! ```compile_fail
+//! # extern crate empty_option;
+//! # use empty_option::Placeholder;
+//! #[derive(Placeholder)]
+//! enum State {
+//!     Idle,
+//!     Running(u32),
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! This crate parses its input by hand, without `syn`, so that `empty-option` stays free of
+//! dependencies.
+
+extern crate proc_macro;
+
+use std::iter::FromIterator;
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+
+const PLACEHOLDER: &str = "::empty_option::Placeholder::placeholder()";
+
+
+#[proc_macro_derive(Placeholder, attributes(placeholder))]
+pub fn derive_placeholder(input: TokenStream) -> TokenStream {
+    let expanded = match expand(input) {
+        Ok(expanded) => expanded,
+        Err(message) => format!("compile_error!({:?});", message),
+    };
+
+    expanded.parse().unwrap()
+}
+
+
+fn expand(input: TokenStream) -> Result<String, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut rest = &tokens[..];
+    let mut sentinel = None;
+
+    // Outer attributes, looking for `#[placeholder(...)]`.
+    while let [TokenTree::Punct(ref pound), TokenTree::Group(ref attr), ref tail @ ..] = *rest {
+        if pound.as_char() != '#' {
+            break;
+        }
+
+        let attr: Vec<TokenTree> = attr.stream().into_iter().collect();
+
+        if let [ref name, TokenTree::Group(ref args)] = attr[..] {
+            if is_ident(name, "placeholder") {
+                sentinel = Some(args.stream().to_string());
+            }
+        }
+
+        rest = tail;
+    }
+
+    rest = skip_visibility(rest);
+
+    let (kind, name, tail) = match *rest {
+        [TokenTree::Ident(ref kind), TokenTree::Ident(ref name), ref tail @ ..] => {
+            (kind.to_string(), name.to_string(), tail)
+        }
+        _ => return Err("expected a struct or enum".to_string()),
+    };
+    rest = tail;
+
+    let (params, tail) = split_generics(rest);
+    rest = tail;
+
+    let where_start = rest.iter().position(|token| is_ident(token, "where"));
+    let where_clause = match where_start {
+        Some(start) => {
+            let len = rest[start..]
+                .iter()
+                .position(|token| is_group(token, Delimiter::Brace) || is_punct(token, ';'))
+                .unwrap_or(rest.len() - start);
+
+            tokens_to_string(&rest[start..start + len])
+        }
+        None => String::new(),
+    };
+
+    let mut bounded = Vec::new();
+
+    let sentinel = match sentinel {
+        Some(sentinel) => sentinel,
+        None if kind == "struct" => {
+            let (sentinel, field_types) = construct_struct(rest, where_start)?;
+
+            // Each field is built from its own placeholder, so generic fields need one too.
+            if !params.is_empty() {
+                bounded = field_types;
+            }
+
+            sentinel
+        }
+        None => {
+            return Err(format!(
+                "`#[derive(Placeholder)]` on `{}` needs a `#[placeholder(...)]` attribute giving \
+                 the value to stand in for it",
+                name
+            ))
+        }
+    };
+
+    let predicates: Vec<String> = bounded
+        .iter()
+        .map(|ty| format!("{}: ::empty_option::Placeholder", ty))
+        .collect();
+
+    let where_clause = match (where_clause.trim_end(), predicates.is_empty()) {
+        (clause, true) => clause.to_string(),
+        ("", false) => format!("where {}", predicates.join(", ")),
+        (clause, false) if clause.ends_with(',') => format!("{} {}", clause, predicates.join(", ")),
+        (clause, false) => format!("{}, {}", clause, predicates.join(", ")),
+    };
+
+    let impl_params: Vec<String> = params.iter().map(|param| param.declaration.clone()).collect();
+    let type_params: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+
+    Ok(format!(
+        "impl<{}> ::empty_option::Placeholder for {}<{}> {} {{ \
+             fn placeholder() -> Self {{ {} }} \
+         }}",
+        impl_params.join(", "),
+        name,
+        type_params.join(", "),
+        where_clause,
+        sentinel
+    ))
+}
+
+
+/// Build `Self` out of the placeholders of each of a struct's fields, returning it along with the
+/// type of each field.
+fn construct_struct(
+    rest: &[TokenTree],
+    where_start: Option<usize>,
+) -> Result<(String, Vec<String>), String> {
+    let before_where = &rest[..where_start.unwrap_or(rest.len())];
+
+    let tuple = before_where.iter().find_map(|token| match *token {
+        TokenTree::Group(ref group) if group.delimiter() == Delimiter::Parenthesis => Some(group),
+        _ => None,
+    });
+
+    if let Some(fields) = tuple {
+        let fields = split_commas(&fields.stream().into_iter().collect::<Vec<_>>());
+        let values = vec![PLACEHOLDER; fields.len()];
+        let types = fields.iter().map(|field| tokens_to_string(skip_field_prefix(field))).collect();
+
+        return Ok((format!("Self({})", values.join(", ")), types));
+    }
+
+    let named = rest.iter().find_map(|token| match *token {
+        TokenTree::Group(ref group) if group.delimiter() == Delimiter::Brace => Some(group),
+        _ => None,
+    });
+
+    let fields = match named {
+        Some(fields) => split_commas(&fields.stream().into_iter().collect::<Vec<_>>()),
+        None => return Ok(("Self".to_string(), Vec::new())),
+    };
+
+    let mut values = Vec::new();
+    let mut types = Vec::new();
+
+    for field in fields {
+        match *skip_field_prefix(&field) {
+            [TokenTree::Ident(ref name), TokenTree::Punct(ref colon), ref ty @ ..]
+                if colon.as_char() == ':' =>
+            {
+                values.push(format!("{}: {}", name, PLACEHOLDER));
+                types.push(tokens_to_string(ty));
+            }
+            _ => return Err("expected a field name".to_string()),
+        }
+    }
+
+    Ok((format!("Self {{ {} }}", values.join(", ")), types))
+}
+
+
+/// Skip the attributes and visibility at the start of a field.
+fn skip_field_prefix(mut field: &[TokenTree]) -> &[TokenTree] {
+    while let [TokenTree::Punct(ref pound), TokenTree::Group(_), ref tail @ ..] = *field {
+        if pound.as_char() != '#' {
+            break;
+        }
+
+        field = tail;
+    }
+
+    skip_visibility(field)
+}
+
+
+fn tokens_to_string(tokens: &[TokenTree]) -> String {
+    TokenStream::from_iter(tokens.iter().cloned()).to_string()
+}
+
+
+struct Param {
+    declaration: String,
+    name: String,
+}
+
+
+/// Split the generic parameters off the front of `rest`, if there are any.
+fn split_generics(rest: &[TokenTree]) -> (Vec<Param>, &[TokenTree]) {
+    if !rest.first().is_some_and(|token| is_punct(token, '<')) {
+        return (Vec::new(), rest);
+    }
+
+    let mut depth = 0;
+    let mut end = 0;
+
+    for (i, token) in rest.iter().enumerate() {
+        match angle(rest, i, token) {
+            1 => depth += 1,
+            -1 => depth -= 1,
+            _ => {}
+        }
+
+        if depth == 0 {
+            end = i;
+            break;
+        }
+    }
+
+    let params = split_commas(&rest[1..end])
+        .into_iter()
+        .map(|param| {
+            // Defaults are not allowed on an impl's parameters.
+            let mut depth = 0;
+            let len = param
+                .iter()
+                .enumerate()
+                .position(|(i, token)| {
+                    depth += angle(&param, i, token);
+                    depth == 0 && is_punct(token, '=')
+                })
+                .unwrap_or(param.len());
+            let declaration = tokens_to_string(&param[..len]);
+
+            let name = match param[..] {
+                [TokenTree::Punct(_), TokenTree::Ident(ref name), ..] => format!("'{}", name),
+                [ref konst, TokenTree::Ident(ref name), ..] if is_ident(konst, "const") =>
+                {
+                    name.to_string()
+                }
+                [ref first, ..] => first.to_string(),
+                [] => String::new(),
+            };
+
+            Param { declaration, name }
+        })
+        .collect();
+
+    (params, &rest[end + 1..])
+}
+
+
+/// Split a token list at its top-level commas, ignoring those nested inside angle brackets.
+fn split_commas(tokens: &[TokenTree]) -> Vec<Vec<TokenTree>> {
+    let mut pieces = vec![Vec::new()];
+    let mut depth = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        depth += angle(tokens, i, token);
+
+        if depth == 0 && is_punct(token, ',') {
+            pieces.push(Vec::new());
+        } else {
+            pieces.last_mut().unwrap().push(token.clone());
+        }
+    }
+
+    pieces.retain(|piece| !piece.is_empty());
+    pieces
+}
+
+
+/// `1` for an opening angle bracket, `-1` for a closing one (but not the `>` of a `->`), and `0`
+/// for anything else.
+fn angle(tokens: &[TokenTree], i: usize, token: &TokenTree) -> i32 {
+    if is_punct(token, '<') {
+        1
+    } else if is_punct(token, '>') && !(i > 0 && is_punct(&tokens[i - 1], '-')) {
+        -1
+    } else {
+        0
+    }
+}
+
+
+fn skip_visibility(tokens: &[TokenTree]) -> &[TokenTree] {
+    match *tokens {
+        [ref vis, TokenTree::Group(ref scope), ref tail @ ..]
+            if is_ident(vis, "pub") && scope.delimiter() == Delimiter::Parenthesis =>
+        {
+            tail
+        }
+        [ref vis, ref tail @ ..] if is_ident(vis, "pub") => tail,
+        _ => tokens,
+    }
+}
+
+
+fn is_ident(token: &TokenTree, name: &str) -> bool {
+    match *token {
+        TokenTree::Ident(ref ident) => ident.to_string() == name,
+        _ => false,
+    }
+}
+
+
+fn is_punct(token: &TokenTree, ch: char) -> bool {
+    match *token {
+        TokenTree::Punct(ref punct) => punct.as_char() == ch,
+        _ => false,
+    }
+}
+
+
+fn is_group(token: &TokenTree, delimiter: Delimiter) -> bool {
+    match *token {
+        TokenTree::Group(ref group) => group.delimiter() == delimiter,
+        _ => false,
+    }
+}
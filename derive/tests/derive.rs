@@ -0,0 +1,102 @@
+extern crate empty_option;
+
+use std::fmt::Debug;
+
+use empty_option::Placeholder;
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+struct Generic<T> {
+    value: T,
+}
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+struct Nested<T> {
+    values: Vec<T>,
+    name: String,
+}
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+struct Tuple<T>(pub Option<T>, Vec<u8>);
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+struct Unit;
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+struct Bounded<T>
+where
+    T: Debug,
+{
+    value: Option<T>,
+}
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+struct TupleBounded<T>(T)
+where
+    T: Debug;
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+#[placeholder(Explicit { value: None })]
+struct Explicit<T> {
+    value: Option<T>,
+}
+
+
+#[derive(Debug, PartialEq, Placeholder)]
+#[placeholder(State::Idle)]
+enum State {
+    Idle,
+    Running(u32),
+}
+
+
+#[test]
+fn generic_struct() {
+    assert_eq!(Generic::<String>::placeholder(), Generic { value: String::new() });
+    assert_eq!(Generic::<Generic<Option<u8>>>::placeholder().value.value, None);
+}
+
+
+#[test]
+fn generic_field_without_bound() {
+    // `Vec<T>` is a placeholder for any `T`, so `Nested<T>` needn't ask more of `T`.
+    struct NotPlaceholder;
+
+    let nested = Nested::<NotPlaceholder>::placeholder();
+
+    assert!(nested.values.is_empty());
+    assert!(nested.name.is_empty());
+}
+
+
+#[test]
+fn tuple_struct() {
+    assert_eq!(Tuple::<u8>::placeholder(), Tuple(None, Vec::new()));
+}
+
+
+#[test]
+fn unit_struct() {
+    assert_eq!(Unit::placeholder(), Unit);
+}
+
+
+#[test]
+fn where_clauses() {
+    assert_eq!(Bounded::<u8>::placeholder(), Bounded { value: None });
+    assert_eq!(TupleBounded::<String>::placeholder(), TupleBounded(String::new()));
+}
+
+
+#[test]
+fn explicit_placeholders() {
+    assert_eq!(Explicit::<u8>::placeholder(), Explicit { value: None });
+    assert_eq!(State::placeholder(), State::Idle);
+    assert_ne!(State::Running(1), State::Idle);
+}
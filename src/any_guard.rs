@@ -0,0 +1,222 @@
+use alloc::boxed::Box;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "std")]
+use {
+    AsyncSlotGuard, HashMapEntryGuard, LeaseGuard, MutexOptionGuard, OwnedAsyncSlotGuard,
+    PoolGuard, SendBackGuard, SharedSlotGuard, TestSlotGuard,
+};
+use {
+    BTreeMapEntryGuard, CopyGuard, FallbackGuard, OpaqueGuardMut, OptionGuard, OptionGuardDeref,
+    OptionGuardMut, OptionGuardRef, PinGuardMut, PlaceholderGuard, PopGuard, RangeGuard,
+    RestoreTuple, TakeGuard, TupleGuardMut,
+};
+#[cfg(feature = "spin")]
+use SpinSlotGuard;
+
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+
+/// A guard which returns its value to wherever it came from when dropped, and so can be erased
+/// into an `AnyGuard` with `AnyGuard::new`.
+///
+/// This trait is sealed, and implemented only for the crate's auto-restoring guards. An
+/// `OptionGuard` panics unless it is given its value back, so it cannot be erased on its own; pair
+/// it with the value to restore with `AnyGuard::restoring` instead.
+pub trait RestoreOnDrop: sealed::Sealed {}
+
+
+macro_rules! restore_on_drop {
+    ($($(#[$attr:meta])* [$($params:tt)*] $guard:ty;)*) => {$(
+        $(#[$attr])*
+        impl<$($params)*> sealed::Sealed for $guard {}
+
+        $(#[$attr])*
+        impl<$($params)*> RestoreOnDrop for $guard {}
+    )*};
+}
+
+
+restore_on_drop! {
+    ['a, T] Restoring<'a, T>;
+    ['a, T] OptionGuardMut<'a, T>;
+    ['a, T] OptionGuardRef<'a, T>;
+    ['a, T] OpaqueGuardMut<'a, T>;
+    ['a, T] OptionGuardDeref<'a, T>;
+    ['a, T] PinGuardMut<'a, T>;
+    ['a, V: RestoreTuple<'a>] TupleGuardMut<'a, V>;
+    ['a, T: Copy] CopyGuard<'a, T>;
+    ['a, T] FallbackGuard<'a, T>;
+    ['a, T] PlaceholderGuard<'a, T>;
+    ['a, T] TakeGuard<'a, T>;
+    ['a, T] PopGuard<'a, T>;
+    ['a, K: Ord, V] RangeGuard<'a, K, V>;
+    ['a, K: Ord, V] BTreeMapEntryGuard<'a, K, V>;
+    #[cfg(feature = "std")]
+    ['a, K: Eq + Hash, V, S: BuildHasher] HashMapEntryGuard<'a, K, V, S>;
+    #[cfg(feature = "std")]
+    ['a, T] SharedSlotGuard<'a, T>;
+    #[cfg(feature = "std")]
+    ['a, T] PoolGuard<'a, T>;
+    #[cfg(feature = "std")]
+    ['a, T] AsyncSlotGuard<'a, T>;
+    #[cfg(feature = "std")]
+    [T] OwnedAsyncSlotGuard<T>;
+    #[cfg(feature = "std")]
+    ['a, T] LeaseGuard<'a, T>;
+    #[cfg(feature = "std")]
+    ['a, T] MutexOptionGuard<'a, T>;
+    #[cfg(feature = "std")]
+    ['a, T: Clone + fmt::Debug] TestSlotGuard<'a, T>;
+    #[cfg(feature = "std")]
+    [T] SendBackGuard<T>;
+    #[cfg(feature = "spin")]
+    ['a, T] SpinSlotGuard<'a, T>;
+}
+
+
+/// An `OptionGuard` paired with the value it is waiting for, restored together on drop.
+struct Restoring<'a, T: 'a> {
+    guard: Option<OptionGuard<'a, T>>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> Drop for Restoring<'a, T> {
+    fn drop(&mut self) {
+        if let (Some(guard), Some(value)) = (self.guard.take(), self.value.take()) {
+            guard.restore(value);
+        }
+    }
+}
+
+
+/// A type-erased guard, which returns whatever value it holds to wherever it came from when
+/// dropped.
+///
+/// Any guard which restores its value on drop (an `OptionGuardMut`, a `SharedSlotGuard`, a
+/// `PoolGuard`, and so on - see `RestoreOnDrop`) can be boxed up with `AnyGuard::new`, and an
+/// `OptionGuard` can be paired with the value it is waiting for with `AnyGuard::restoring`. Guards
+/// over values of different types can then be stashed together in one collection, to be restored
+/// later.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{AnyGuard, EmptyOptionExt};
+/// let mut number = Some(5);
+/// let mut name = Some(String::from("five"));
+///
+/// {
+///     let mut pending: Vec<AnyGuard> = Vec::new();
+///
+///     let (guard, five) = number.steal();
+///     pending.push(AnyGuard::restoring(guard, five + 1));
+///
+///     let mut stolen = name.steal_mut();
+///     stolen.push('!');
+///     pending.push(stolen.into());
+///
+///     // Restores both values.
+///     drop(pending);
+/// }
+///
+/// assert_eq!(number, Some(6));
+/// assert_eq!(name, Some(String::from("five!")));
+/// ```
+///
+/// A bare `OptionGuard` could never be restored once erased, so it is rejected:
+///
+/// ```compile_fail
+/// # use empty_option::{AnyGuard, EmptyOptionExt};
+/// let mut number = Some(5);
+/// let (guard, _) = number.steal();
+///
+/// AnyGuard::new(guard);
+/// ```
+pub struct AnyGuard<'a> {
+    guard: Box<dyn RestoreOnDrop + 'a>,
+}
+
+
+impl<'a> AnyGuard<'a> {
+    /// Erase the type of a guard which restores its value when dropped.
+    pub fn new<G: RestoreOnDrop + 'a>(guard: G) -> AnyGuard<'a> {
+        AnyGuard {
+            guard: Box::new(guard),
+        }
+    }
+
+
+    /// Erase the type of an `OptionGuard`, along with the value to restore through it when the
+    /// `AnyGuard` is dropped.
+    pub fn restoring<T: 'a>(guard: OptionGuard<'a, T>, value: T) -> AnyGuard<'a> {
+        AnyGuard::new(Restoring {
+            guard: Some(guard),
+            value: Some(value),
+        })
+    }
+
+
+    /// Restore the value right away. Equivalent to dropping the `AnyGuard`.
+    pub fn restore(self) {
+        drop(self.guard);
+    }
+}
+
+
+impl<'a, T: 'a> From<OptionGuardMut<'a, T>> for AnyGuard<'a> {
+    fn from(guard: OptionGuardMut<'a, T>) -> AnyGuard<'a> {
+        AnyGuard::new(guard)
+    }
+}
+
+
+impl<'a, T: 'a> From<(OptionGuard<'a, T>, T)> for AnyGuard<'a> {
+    fn from((guard, value): (OptionGuard<'a, T>, T)) -> AnyGuard<'a> {
+        AnyGuard::restoring(guard, value)
+    }
+}
+
+
+impl<'a> fmt::Debug for AnyGuard<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AnyGuard").finish()
+    }
+}
+
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use EmptyOptionExt;
+    use SharedSlot;
+
+    #[test]
+    fn heterogeneous_guards() {
+        let mut number = Some(1);
+        let shared = SharedSlot::new('a');
+
+        {
+            let guards: Vec<AnyGuard> = vec![
+                number.steal().into(),
+                AnyGuard::new(shared.try_steal().unwrap()),
+            ];
+
+            assert!(!shared.is_occupied());
+
+            for guard in guards {
+                guard.restore();
+            }
+        }
+
+        assert_eq!(number, Some(1));
+        assert!(shared.is_occupied());
+    }
+}
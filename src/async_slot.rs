@@ -0,0 +1,946 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{ready, Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use poison;
+use timer::Timer;
+
+
+/// The tasks waiting on some condition, each identified by a key so that a pending future can
+/// update or remove its registration.
+struct WaitList {
+    entries: Vec<(usize, Waker)>,
+    next_key: usize,
+}
+
+
+impl WaitList {
+    const fn new() -> WaitList {
+        WaitList {
+            entries: Vec::new(),
+            next_key: 0,
+        }
+    }
+
+
+    fn register(&mut self, key: &mut Option<usize>, waker: &Waker) {
+        let key = *key.get_or_insert_with(|| {
+            let key = self.next_key;
+            self.next_key = self.next_key.wrapping_add(1);
+            key
+        });
+
+        match self.entries.iter_mut().find(|&&mut (entry, _)| entry == key) {
+            Some(&mut (_, ref mut registered)) => registered.clone_from(waker),
+            None => self.entries.push((key, waker.clone())),
+        }
+    }
+
+
+    fn remove(&mut self, key: Option<usize>) {
+        if let Some(key) = key {
+            self.entries.retain(|&(entry, _)| entry != key);
+        }
+    }
+
+
+    fn wake_all(&mut self) {
+        for (_, waker) in self.entries.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+
+struct State<T> {
+    value: Option<T>,
+    stolen: bool,
+    stealers: WaitList,
+    watchers: WaitList,
+    version: u64,
+}
+
+
+impl<T> State<T> {
+    fn take(&mut self) -> Option<T> {
+        let value = self.value.take()?;
+
+        self.stolen = true;
+        self.transition();
+
+        Some(value)
+    }
+
+
+    fn fill(&mut self, value: Option<T>) {
+        self.value = value;
+        self.stolen = false;
+        self.stealers.wake_all();
+        self.transition();
+    }
+
+
+    fn transition(&mut self) {
+        self.version = self.version.wrapping_add(1);
+        self.watchers.wake_all();
+    }
+
+
+    fn occupancy(&self) -> Occupancy {
+        if self.value.is_some() {
+            Occupancy::Occupied
+        } else if self.stolen {
+            Occupancy::Stolen
+        } else {
+            Occupancy::Vacant
+        }
+    }
+}
+
+
+/// The state of an `AsyncSlot`, as reported by `AsyncSlot::occupancy` and `OccupancyWatch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Occupancy {
+    /// The slot holds a value which can be stolen.
+    Occupied,
+
+    /// The slot's value is currently stolen, and will be returned when its guard is dropped.
+    Stolen,
+
+    /// The slot holds no value, and will not until one is `put` into it.
+    Vacant,
+}
+
+
+/// A slot shared between asynchronous tasks, whose value can be stolen by awaiting
+/// `AsyncSlot::steal`. While the value is stolen, other tasks awaiting a steal are suspended; they
+/// are woken as soon as the `AsyncSlotGuard` holding the value is dropped and the value returned.
+///
+/// `AsyncSlot` is built only on `std::task`, so it works with any executor (tokio, async-std,
+/// smol, or a hand-rolled one) without depending on any of them.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::AsyncSlot;
+/// # fn block_on<F: std::future::Future>(f: F) -> F::Output {
+/// #     let mut f = std::pin::pin!(f);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop { if let std::task::Poll::Ready(v) = f.as_mut().poll(&mut cx) { return v; } }
+/// # }
+/// let slot = AsyncSlot::new(5);
+///
+/// {
+///     let mut stolen = block_on(slot.steal());
+///
+///     assert_eq!(*stolen, 5);
+///
+///     // Nobody else can take the value while we hold it.
+///     assert!(slot.try_steal().is_none());
+///
+///     *stolen = 6;
+/// }
+///
+/// assert_eq!(slot.into_inner(), Some(6));
+/// ```
+pub struct AsyncSlot<T> {
+    state: Mutex<State<T>>,
+}
+
+
+impl<T> AsyncSlot<T> {
+    /// Create a new slot holding a value.
+    pub const fn new(value: T) -> AsyncSlot<T> {
+        AsyncSlot::from_option(Some(value))
+    }
+
+
+    /// Create a new, vacant slot. Steals will wait until a value is `put` into it.
+    pub const fn empty() -> AsyncSlot<T> {
+        AsyncSlot::from_option(None)
+    }
+
+
+    const fn from_option(value: Option<T>) -> AsyncSlot<T> {
+        AsyncSlot {
+            state: Mutex::new(State {
+                value,
+                stolen: false,
+                stealers: WaitList::new(),
+                watchers: WaitList::new(),
+                version: 0,
+            }),
+        }
+    }
+
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        poison::lock(&self.state)
+    }
+
+
+    /// Wait until the slot holds a value, and then take it, providing a guard which returns the
+    /// value to the slot when dropped unless consumed by `AsyncSlotGuard::into_inner`.
+    pub fn steal<'a>(&'a self) -> AsyncSteal<'a, T> {
+        AsyncSteal {
+            slot: self,
+            waiter: None,
+        }
+    }
+
+
+    /// Like `AsyncSlot::steal`, but giving up with a `StealTimedOut` error if the slot does not
+    /// hold a value within `timeout`, so that tasks competing for the value can fail fast. A
+    /// timeout too long to be represented as a deadline never expires.
+    pub fn steal_timeout<'a>(&'a self, timeout: Duration) -> AsyncStealTimeout<'a, T> {
+        AsyncStealTimeout {
+            slot: self,
+            waiter: None,
+            deadline: Instant::now().checked_add(timeout),
+            timer: None,
+        }
+    }
+
+
+    /// Take the value out of the slot if it holds one right now.
+    pub fn try_steal<'a>(&'a self) -> Option<AsyncSlotGuard<'a, T>> {
+        let value = self.lock().take()?;
+
+        Some(AsyncSlotGuard::new(self, value))
+    }
+
+
+    /// Fill a vacant slot with a value, waking any tasks waiting to steal it. If the slot already
+    /// holds a value, or its value is currently stolen, the value is handed back.
+    pub fn put(&self, value: T) -> Result<(), T> {
+        let mut state = self.lock();
+
+        if state.stolen || state.value.is_some() {
+            return Err(value);
+        }
+
+        state.fill(Some(value));
+
+        Ok(())
+    }
+
+
+    /// Returns `true` if the slot holds a value which is not currently stolen.
+    pub fn is_occupied(&self) -> bool {
+        self.lock().value.is_some()
+    }
+
+
+    /// Whether the slot currently holds a value, has had it stolen, or is vacant.
+    pub fn occupancy(&self) -> Occupancy {
+        self.lock().occupancy()
+    }
+
+
+    /// Start watching the slot for occupancy changes. Watching is entirely optional: a slot
+    /// which nobody watches does no extra work beyond bumping a counter.
+    pub fn watch<'a>(&'a self) -> OccupancyWatch<'a, T> {
+        OccupancyWatch {
+            slot: self,
+            seen: self.lock().version,
+        }
+    }
+
+
+    /// Consume the slot, returning the value in it, if any.
+    pub fn into_inner(self) -> Option<T> {
+        poison::into_inner(self.state).value
+    }
+
+
+    /// Wait until the slot holds a value, and then take it, providing a guard which keeps the slot
+    /// alive through an `Arc`. The guard is `'static` whenever `T` is, so it can be moved into a
+    /// spawned task.
+    pub fn steal_owned(self: Arc<Self>) -> AsyncStealOwned<T> {
+        AsyncStealOwned {
+            slot: Some(self),
+            waiter: None,
+        }
+    }
+
+
+    /// Take the value out of the slot if it holds one right now, providing a guard which keeps the
+    /// slot alive through an `Arc`.
+    pub fn try_steal_owned(self: Arc<Self>) -> Option<OwnedAsyncSlotGuard<T>> {
+        let value = self.lock().take()?;
+
+        Some(OwnedAsyncSlotGuard::new(self, value))
+    }
+
+
+    fn poll_steal(&self, waiter: &mut Option<usize>, cx: &mut Context) -> Poll<T> {
+        let mut state = self.lock();
+
+        match state.take() {
+            Some(value) => {
+                state.stealers.remove(waiter.take());
+                Poll::Ready(value)
+            }
+            None => {
+                state.stealers.register(waiter, cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+
+
+    fn deregister(&self, waiter: Option<usize>) {
+        self.lock().stealers.remove(waiter);
+    }
+
+
+    fn check_in(&self, value: Option<T>) {
+        self.lock().fill(value);
+    }
+}
+
+
+impl<T> fmt::Debug for AsyncSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.lock();
+
+        f.debug_struct("AsyncSlot")
+            .field("occupancy", &state.occupancy())
+            .field("waiters", &state.stealers.entries.len())
+            .finish()
+    }
+}
+
+
+/// The future returned by `AsyncSlot::steal`, resolving to an `AsyncSlotGuard` once the slot
+/// holds a value.
+pub struct AsyncSteal<'a, T: 'a> {
+    slot: &'a AsyncSlot<T>,
+    waiter: Option<usize>,
+}
+
+
+impl<'a, T> Future for AsyncSteal<'a, T> {
+    type Output = AsyncSlotGuard<'a, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<AsyncSlotGuard<'a, T>> {
+        let slot = self.slot;
+
+        slot.poll_steal(&mut self.waiter, cx)
+            .map(|value| AsyncSlotGuard::new(slot, value))
+    }
+}
+
+
+impl<'a, T> Drop for AsyncSteal<'a, T> {
+    fn drop(&mut self) {
+        self.slot.deregister(self.waiter);
+    }
+}
+
+
+/// The future returned by `AsyncSlot::steal_timeout`, resolving to an `AsyncSlotGuard` once the
+/// slot holds a value, or to a `StealTimedOut` error once the timeout has passed.
+pub struct AsyncStealTimeout<'a, T: 'a> {
+    slot: &'a AsyncSlot<T>,
+    waiter: Option<usize>,
+    deadline: Option<Instant>,
+    timer: Option<Arc<Timer>>,
+}
+
+
+impl<'a, T> Future for AsyncStealTimeout<'a, T> {
+    type Output = Result<AsyncSlotGuard<'a, T>, StealTimedOut>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let slot = this.slot;
+
+        if let Poll::Ready(value) = slot.poll_steal(&mut this.waiter, cx) {
+            return Poll::Ready(Ok(AsyncSlotGuard::new(slot, value)));
+        }
+
+        let deadline = match this.deadline {
+            Some(deadline) => deadline,
+            None => return Poll::Pending,
+        };
+
+        if Instant::now() >= deadline {
+            slot.deregister(this.waiter.take());
+            return Poll::Ready(Err(StealTimedOut));
+        }
+
+        match this.timer {
+            Some(ref timer) => timer.set_waker(cx.waker()),
+            None => this.timer = Some(Timer::start(deadline, cx.waker())),
+        }
+
+        Poll::Pending
+    }
+}
+
+
+impl<'a, T> Drop for AsyncStealTimeout<'a, T> {
+    fn drop(&mut self) {
+        self.slot.deregister(self.waiter);
+
+        if let Some(ref timer) = self.timer {
+            timer.cancel();
+        }
+    }
+}
+
+
+/// The error returned by `AsyncSlot::steal_timeout` when the slot did not hold a value in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StealTimedOut;
+
+
+impl fmt::Display for StealTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("timed out waiting to steal from an AsyncSlot")
+    }
+}
+
+
+impl Error for StealTimedOut {}
+
+
+/// The future returned by `AsyncSlot::steal_owned`, resolving to an `OwnedAsyncSlotGuard` once
+/// the slot holds a value.
+pub struct AsyncStealOwned<T> {
+    slot: Option<Arc<AsyncSlot<T>>>,
+    waiter: Option<usize>,
+}
+
+
+impl<T> Future for AsyncStealOwned<T> {
+    type Output = OwnedAsyncSlotGuard<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<OwnedAsyncSlotGuard<T>> {
+        let this = &mut *self;
+        let value = {
+            let slot = this.slot.as_ref().expect("`AsyncStealOwned` polled after completion");
+
+            match slot.poll_steal(&mut this.waiter, cx) {
+                Poll::Ready(value) => value,
+                Poll::Pending => return Poll::Pending,
+            }
+        };
+
+        Poll::Ready(OwnedAsyncSlotGuard::new(this.slot.take().unwrap(), value))
+    }
+}
+
+
+impl<T> Drop for AsyncStealOwned<T> {
+    fn drop(&mut self) {
+        if let Some(ref slot) = self.slot {
+            slot.deregister(self.waiter);
+        }
+    }
+}
+
+
+/// A subscription to occupancy changes of an `AsyncSlot`, created by `AsyncSlot::watch`. Each
+/// steal, restore, consumption, or refill of the slot is a change.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{AsyncSlot, Occupancy};
+/// # fn block_on<F: std::future::Future>(f: F) -> F::Output {
+/// #     let mut f = std::pin::pin!(f);
+/// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+/// #     loop { if let std::task::Poll::Ready(v) = f.as_mut().poll(&mut cx) { return v; } }
+/// # }
+/// let slot = AsyncSlot::new(5);
+/// let mut watch = slot.watch();
+///
+/// let stolen = slot.try_steal().unwrap();
+/// assert_eq!(block_on(watch.changed()), Occupancy::Stolen);
+///
+/// drop(stolen);
+/// assert_eq!(block_on(watch.changed()), Occupancy::Occupied);
+/// ```
+pub struct OccupancyWatch<'a, T: 'a> {
+    slot: &'a AsyncSlot<T>,
+    seen: u64,
+}
+
+
+impl<'a, T> OccupancyWatch<'a, T> {
+    /// Wait until the slot's occupancy changes from when it was last observed by this watch, and
+    /// return the slot's current occupancy. If several changes happened in the meantime, they are
+    /// reported as one.
+    pub fn changed<'w>(&'w mut self) -> OccupancyChanged<'w, 'a, T> {
+        OccupancyChanged {
+            watch: self,
+            waiter: None,
+        }
+    }
+
+
+    /// The slot's current occupancy. This marks the current state as observed.
+    pub fn occupancy(&mut self) -> Occupancy {
+        let state = self.slot.lock();
+        self.seen = state.version;
+        state.occupancy()
+    }
+}
+
+
+impl<'a, T> fmt::Debug for OccupancyWatch<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OccupancyWatch")
+            .field("seen", &self.seen)
+            .finish()
+    }
+}
+
+
+/// The future returned by `OccupancyWatch::changed`.
+pub struct OccupancyChanged<'w, 'a: 'w, T: 'a> {
+    watch: &'w mut OccupancyWatch<'a, T>,
+    waiter: Option<usize>,
+}
+
+
+impl<'w, 'a, T> Future for OccupancyChanged<'w, 'a, T> {
+    type Output = Occupancy;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Occupancy> {
+        let this = &mut *self;
+        let mut state = this.watch.slot.lock();
+
+        if state.version == this.watch.seen {
+            state.watchers.register(&mut this.waiter, cx.waker());
+            return Poll::Pending;
+        }
+
+        state.watchers.remove(this.waiter.take());
+        this.watch.seen = state.version;
+
+        Poll::Ready(state.occupancy())
+    }
+}
+
+
+impl<'w, 'a, T> Drop for OccupancyChanged<'w, 'a, T> {
+    fn drop(&mut self) {
+        if self.waiter.is_some() {
+            self.watch.slot.lock().watchers.remove(self.waiter);
+        }
+    }
+}
+
+
+/// A value stolen from an `AsyncSlot`. `AsyncSlotGuard<T>` dereferences to a `T`, and the inner
+/// `T` can be moved out with `AsyncSlotGuard::into_inner`, leaving the slot vacant. When dropped,
+/// the `AsyncSlotGuard` returns the value to the slot and wakes any tasks waiting to steal it.
+pub struct AsyncSlotGuard<'a, T: 'a> {
+    slot: &'a AsyncSlot<T>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> AsyncSlotGuard<'a, T> {
+    fn new(slot: &'a AsyncSlot<T>, value: T) -> AsyncSlotGuard<'a, T> {
+        AsyncSlotGuard {
+            slot,
+            value: Some(value),
+        }
+    }
+
+
+    /// Keep the value stolen from the slot and do not return it. The slot is left vacant, ready to
+    /// be refilled with `AsyncSlot::put`.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+
+
+    /// Refill the slot with the value `future` produces, rather than the stolen one. The slot
+    /// stays stolen while `future` runs, so no other task sees it until the new value is
+    /// installed. If `future` fails, or the `RestoreFrom` is dropped before it completes,
+    /// `on_failure` decides what becomes of the slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::{AsyncSlot, OnRestoreFailure};
+    /// # fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    /// #     let mut f = std::pin::pin!(f);
+    /// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    /// #     loop { if let std::task::Poll::Ready(v) = f.as_mut().poll(&mut cx) { return v; } }
+    /// # }
+    /// use std::future;
+    ///
+    /// let token = AsyncSlot::new(String::from("expired"));
+    ///
+    /// let stolen = token.try_steal().unwrap();
+    /// let refresh = future::ready(Ok::<_, ()>(String::from("fresh")));
+    /// assert_eq!(block_on(stolen.restore_from(refresh, OnRestoreFailure::KeepOld)), Ok(()));
+    ///
+    /// let stolen = token.try_steal().unwrap();
+    /// let refresh = future::ready(Err::<String, _>("unauthorized"));
+    /// let failed = block_on(stolen.restore_from(refresh, OnRestoreFailure::KeepOld));
+    /// assert_eq!(failed, Err("unauthorized"));
+    ///
+    /// assert_eq!(token.into_inner().unwrap(), "fresh");
+    /// ```
+    pub fn restore_from<F, E>(
+        self,
+        future: F,
+        on_failure: OnRestoreFailure,
+    ) -> RestoreFrom<'a, T, F>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        RestoreFrom {
+            guard: Some(self),
+            future,
+            on_failure,
+        }
+    }
+}
+
+
+impl<'a, T> Drop for AsyncSlotGuard<'a, T> {
+    fn drop(&mut self) {
+        self.slot.check_in(self.value.take());
+    }
+}
+
+
+impl<'a, T> Deref for AsyncSlotGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for AsyncSlotGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+/// What `AsyncSlotGuard::restore_from` does with the slot when the replacement value never
+/// arrives, because the future failed or was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OnRestoreFailure {
+    /// Return the stolen value to the slot, as though it had never been stolen.
+    KeepOld,
+
+    /// Drop the stolen value and leave the slot vacant, ready to be refilled with `AsyncSlot::put`.
+    Vacate,
+}
+
+
+impl OnRestoreFailure {
+    fn apply<'a, T>(self, guard: AsyncSlotGuard<'a, T>) {
+        match self {
+            OnRestoreFailure::KeepOld => drop(guard),
+            OnRestoreFailure::Vacate => drop(guard.into_inner()),
+        }
+    }
+}
+
+
+/// The future returned by `AsyncSlotGuard::restore_from`, resolving once the replacement value has
+/// been installed in the slot, or to the error the replacement future failed with.
+pub struct RestoreFrom<'a, T: 'a, F> {
+    guard: Option<AsyncSlotGuard<'a, T>>,
+    future: F,
+    on_failure: OnRestoreFailure,
+}
+
+
+impl<'a, T, F, E> Future for RestoreFrom<'a, T, F>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    type Output = Result<(), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), E>> {
+        // The future is never moved out of the `RestoreFrom`, so it stays pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let result = ready!(unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx));
+        let mut guard = this
+            .guard
+            .take()
+            .expect("`RestoreFrom` polled after completion");
+
+        match result {
+            Ok(value) => {
+                // Dropping the guard installs the new value.
+                guard.value = Some(value);
+                Poll::Ready(Ok(()))
+            }
+            Err(error) => {
+                this.on_failure.apply(guard);
+                Poll::Ready(Err(error))
+            }
+        }
+    }
+}
+
+
+impl<'a, T, F> Drop for RestoreFrom<'a, T, F> {
+    fn drop(&mut self) {
+        if let Some(guard) = self.guard.take() {
+            self.on_failure.apply(guard);
+        }
+    }
+}
+
+
+/// A value stolen from an `AsyncSlot` through an `Arc`, produced by `AsyncSlot::steal_owned`. It
+/// behaves exactly like an `AsyncSlotGuard`, but owns a reference to the slot instead of borrowing
+/// it.
+pub struct OwnedAsyncSlotGuard<T> {
+    slot: Arc<AsyncSlot<T>>,
+    value: Option<T>,
+}
+
+
+impl<T> OwnedAsyncSlotGuard<T> {
+    fn new(slot: Arc<AsyncSlot<T>>, value: T) -> OwnedAsyncSlotGuard<T> {
+        OwnedAsyncSlotGuard {
+            slot,
+            value: Some(value),
+        }
+    }
+
+
+    /// Keep the value stolen from the slot and do not return it. The slot is left vacant, ready to
+    /// be refilled with `AsyncSlot::put`.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+
+
+    /// The slot this value was stolen from.
+    pub fn slot(&self) -> &Arc<AsyncSlot<T>> {
+        &self.slot
+    }
+}
+
+
+impl<T> Drop for OwnedAsyncSlotGuard<T> {
+    fn drop(&mut self) {
+        self.slot.check_in(self.value.take());
+    }
+}
+
+
+impl<T> Deref for OwnedAsyncSlotGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<T> DerefMut for OwnedAsyncSlotGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use std::future::{pending, ready};
+
+    use test_util::{block_on, poll_once};
+
+    #[test]
+    fn steal_waits_for_restore() {
+        let slot = Arc::new(AsyncSlot::new(0));
+        let guard = slot.try_steal().unwrap();
+
+        let waiter = {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                let mut stolen = block_on(slot.steal());
+                *stolen += 1;
+            })
+        };
+
+        drop(guard);
+        waiter.join().unwrap();
+
+        assert_eq!(Arc::try_unwrap(slot).unwrap().into_inner(), Some(1));
+    }
+
+    #[test]
+    fn put_fills_vacancy() {
+        let slot = AsyncSlot::empty();
+
+        let mut steal = Box::pin(slot.steal());
+        assert!(poll_once(&mut steal).is_pending());
+
+        assert_eq!(slot.put(5), Ok(()));
+        assert_eq!(slot.put(6), Err(6));
+
+        let stolen = block_on(steal);
+        assert_eq!(slot.put(7), Err(7));
+        assert_eq!(stolen.into_inner(), 5);
+
+        assert!(!slot.is_occupied());
+        assert_eq!(slot.put(8), Ok(()));
+    }
+
+    #[test]
+    fn owned_guard_moves_to_thread() {
+        let slot = Arc::new(AsyncSlot::new(0));
+        let mut guard = slot.clone().try_steal_owned().unwrap();
+
+        assert!(slot.clone().try_steal_owned().is_none());
+
+        thread::spawn(move || *guard += 1).join().unwrap();
+
+        let stolen = block_on(slot.clone().steal_owned());
+        assert_eq!(*stolen, 1);
+        assert!(Arc::ptr_eq(stolen.slot(), &slot));
+    }
+
+    #[test]
+    fn watch_sees_transitions() {
+        let slot = Arc::new(AsyncSlot::new(0));
+
+        let watcher = {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                let mut watch = slot.watch();
+                let mut seen = vec![watch.occupancy()];
+
+                while seen.last() != Some(&Occupancy::Vacant) {
+                    seen.push(block_on(watch.changed()));
+                }
+
+                seen
+            })
+        };
+
+        // Wait for the watcher to start watching before making any changes.
+        while slot.lock().watchers.entries.is_empty() {
+            thread::yield_now();
+        }
+
+        slot.try_steal().unwrap().into_inner();
+
+        let seen = watcher.join().unwrap();
+
+        assert_eq!(seen[0], Occupancy::Occupied);
+        assert_eq!(seen.last(), Some(&Occupancy::Vacant));
+        assert_eq!(slot.occupancy(), Occupancy::Vacant);
+    }
+
+    #[test]
+    fn steal_timeout_fails_fast() {
+        let slot = AsyncSlot::new(5);
+        let guard = slot.try_steal().unwrap();
+
+        let timed_out = block_on(slot.steal_timeout(Duration::from_millis(10)));
+        assert_eq!(timed_out.err(), Some(StealTimedOut));
+        assert_eq!(slot.lock().stealers.entries.len(), 0);
+
+        drop(guard);
+
+        let stolen = block_on(slot.steal_timeout(Duration::from_secs(60))).unwrap();
+        assert_eq!(*stolen, 5);
+    }
+
+    #[test]
+    fn steal_timeout_without_deadline() {
+        let slot = AsyncSlot::new(5);
+        let guard = slot.try_steal().unwrap();
+
+        {
+            let mut steal = Box::pin(slot.steal_timeout(Duration::MAX));
+            assert!(poll_once(&mut steal).is_pending());
+            assert!(steal.timer.is_none());
+        }
+
+        drop(guard);
+
+        assert_eq!(*block_on(slot.steal_timeout(Duration::MAX)).unwrap(), 5);
+    }
+
+    #[test]
+    fn cancelled_timers_leave_queue() {
+        let slot = AsyncSlot::new(5);
+        let guard = slot.try_steal().unwrap();
+
+        let mut steals: Vec<_> = (0..100)
+            .map(|_| Box::pin(slot.steal_timeout(Duration::from_secs(60))))
+            .collect();
+
+        for steal in &mut steals {
+            assert!(poll_once(steal).is_pending());
+        }
+
+        let first = steals[0].timer.clone().unwrap();
+        assert!(first.is_queued());
+
+        // Cancelled steals take their timers out of the queue.
+        drop(steals);
+        assert!(!first.is_queued());
+
+        drop(guard);
+    }
+
+    #[test]
+    fn restore_from_policies() {
+        let slot = AsyncSlot::new(1);
+
+        let refill = block_on(slot.try_steal().unwrap().restore_from(
+            ready(Ok::<_, ()>(2)),
+            OnRestoreFailure::Vacate,
+        ));
+        assert_eq!(refill, Ok(()));
+        assert_eq!(*slot.try_steal().unwrap(), 2);
+
+        {
+            let stolen = slot.try_steal().unwrap();
+            let never = pending::<Result<_, ()>>();
+            let mut restore = stolen.restore_from(never, OnRestoreFailure::Vacate);
+
+            assert!(poll_once(&mut restore).is_pending());
+            assert_eq!(slot.occupancy(), Occupancy::Stolen);
+        }
+
+        assert_eq!(slot.occupancy(), Occupancy::Vacant);
+    }
+
+    #[test]
+    fn cancelled_steal_deregisters() {
+        let slot = AsyncSlot::new(5);
+        let guard = slot.try_steal().unwrap();
+
+        {
+            let mut steal = Box::pin(slot.steal());
+            assert!(poll_once(&mut steal).is_pending());
+        }
+
+        assert_eq!(slot.lock().stealers.entries.len(), 0);
+        drop(guard);
+    }
+}
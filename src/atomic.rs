@@ -0,0 +1,256 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use violation;
+
+
+/// Marker for `Copy` types with no padding or otherwise uninitialized bytes, which can therefore
+/// be packed bit-for-bit into an integer.
+///
+/// # Safety
+///
+/// Every byte of every value of the implementing type must be initialized.
+pub unsafe trait NoPadding: Copy {}
+
+
+unsafe impl NoPadding for u8 {}
+unsafe impl NoPadding for u16 {}
+unsafe impl NoPadding for u32 {}
+unsafe impl NoPadding for i8 {}
+unsafe impl NoPadding for i16 {}
+unsafe impl NoPadding for i32 {}
+unsafe impl NoPadding for f32 {}
+unsafe impl NoPadding for bool {}
+unsafe impl NoPadding for char {}
+unsafe impl<T: NoPadding, const N: usize> NoPadding for [T; N] {}
+
+
+const TAG_BYTE: usize = 7;
+const EMPTY: u64 = 0;
+
+
+/// A lock-free slot for small `Copy` values, packed together with an occupancy tag into a single
+/// `AtomicU64`. Values must be at most seven bytes in size; the eighth byte holds the tag.
+///
+/// Stealing and restoring are each a single atomic operation, so an `AtomicSmallSlot` can be
+/// shared between threads (or with an interrupt handler) without any locking. As with
+/// `OptionGuard`, the `AtomicSmallGuard` produced by a steal panics if it is dropped without a
+/// value being restored.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::AtomicSmallSlot;
+/// let slot = AtomicSmallSlot::new(5u32);
+///
+/// {
+///     let (guard, five) = slot.steal();
+///
+///     assert_eq!(five, 5);
+///
+///     // While the value is stolen, nobody else can take it.
+///     assert!(slot.try_steal().is_none());
+///
+///     guard.restore(6);
+/// }
+///
+/// assert_eq!(slot.load(), Some(6));
+/// ```
+pub struct AtomicSmallSlot<T: NoPadding> {
+    bits: AtomicU64,
+    _marker: PhantomData<T>,
+}
+
+
+impl<T: NoPadding> AtomicSmallSlot<T> {
+    const FITS: () = assert!(
+        mem::size_of::<T>() <= TAG_BYTE,
+        "AtomicSmallSlot values must be at most seven bytes in size"
+    );
+
+
+    /// Create a new slot holding a value.
+    pub const fn new(value: T) -> AtomicSmallSlot<T> {
+        AtomicSmallSlot::from_bits(pack(value))
+    }
+
+
+    /// Create a new, empty slot.
+    pub const fn empty() -> AtomicSmallSlot<T> {
+        AtomicSmallSlot::from_bits(EMPTY)
+    }
+
+
+    const fn from_bits(bits: u64) -> AtomicSmallSlot<T> {
+        let () = Self::FITS;
+
+        AtomicSmallSlot {
+            bits: AtomicU64::new(bits),
+            _marker: PhantomData,
+        }
+    }
+
+
+    /// Take the value out of the slot, providing a guard which panics if a value is not restored.
+    /// Panics if the slot is empty.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on an empty slot, which the `strict` feature forbids")
+    )]
+    #[track_caller]
+    pub fn steal<'a>(&'a self) -> (AtomicSmallGuard<'a, T>, T) {
+        match self.try_steal() {
+            Some(stolen) => stolen,
+            None => violation::steal_from_none::<T, _>(self),
+        }
+    }
+
+
+    /// Take the value out of the slot if it is occupied, providing a guard which panics if a value
+    /// is not restored.
+    pub fn try_steal<'a>(&'a self) -> Option<(AtomicSmallGuard<'a, T>, T)> {
+        let value = unpack(self.bits.swap(EMPTY, Ordering::AcqRel))?;
+
+        Some((AtomicSmallGuard { slot: self }, value))
+    }
+
+
+    /// Get a copy of the value in the slot without taking it, if the slot is occupied.
+    pub fn load(&self) -> Option<T> {
+        unpack(self.bits.load(Ordering::Acquire))
+    }
+
+
+    /// Returns `true` if the slot currently holds a value.
+    pub fn is_occupied(&self) -> bool {
+        self.bits.load(Ordering::Acquire) != EMPTY
+    }
+
+
+    /// Consume the slot, returning the value in it if it is occupied.
+    pub fn into_inner(self) -> Option<T> {
+        unpack(self.bits.into_inner())
+    }
+}
+
+
+impl<T: NoPadding + fmt::Debug> fmt::Debug for AtomicSmallSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AtomicSmallSlot").field(&self.load()).finish()
+    }
+}
+
+
+const fn pack<T: NoPadding>(value: T) -> u64 {
+    let mut bytes = [0u8; 8];
+
+    unsafe {
+        ptr::copy_nonoverlapping(
+            &value as *const T as *const u8,
+            bytes.as_mut_ptr(),
+            mem::size_of::<T>(),
+        );
+    }
+
+    bytes[TAG_BYTE] = 1;
+
+    u64::from_ne_bytes(bytes)
+}
+
+
+fn unpack<T: NoPadding>(bits: u64) -> Option<T> {
+    let bytes = bits.to_ne_bytes();
+
+    if bytes[TAG_BYTE] == 0 {
+        return None;
+    }
+
+    // Only ever called on bits produced by `pack`, so this is a valid `T`.
+    Some(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+
+/// A value stolen from an `AtomicSmallSlot`. On `Drop`, `AtomicSmallGuard` will panic - in order
+/// to prevent a panic, a value must be moved back in with `AtomicSmallGuard::restore`.
+pub struct AtomicSmallGuard<'a, T: NoPadding + 'a> {
+    slot: &'a AtomicSmallSlot<T>,
+}
+
+
+impl<'a, T: NoPadding> AtomicSmallGuard<'a, T> {
+    /// Restore a value to the `AtomicSmallSlot`.
+    pub fn restore(self, value: T) {
+        self.slot.bits.store(pack(value), Ordering::Release);
+
+        mem::forget(self);
+    }
+}
+
+
+impl<'a, T: NoPadding> Drop for AtomicSmallGuard<'a, T> {
+    fn drop(&mut self) {
+        violation::guard_leaked::<T, _>(self.slot);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn round_trip() {
+        let slot = AtomicSmallSlot::new([b'a', b'b']);
+
+        let (guard, value) = slot.steal();
+
+        assert_eq!(value, [b'a', b'b']);
+        assert!(!slot.is_occupied());
+
+        guard.restore([0, 0]);
+
+        // An all-zero value is still distinguishable from an empty slot.
+        assert_eq!(slot.into_inner(), Some([0, 0]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn steal_from_empty() {
+        let slot = AtomicSmallSlot::<u32>::empty();
+
+        slot.steal();
+    }
+
+    #[test]
+    fn contended_increment() {
+        let slot = Arc::new(AtomicSmallSlot::new(0u32));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let slot = slot.clone();
+                thread::spawn(move || {
+                    let mut done = 0;
+
+                    while done < 100 {
+                        if let Some((guard, value)) = slot.try_steal() {
+                            guard.restore(value + 1);
+                            done += 1;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(slot.load(), Some(400));
+    }
+}
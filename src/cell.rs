@@ -0,0 +1,229 @@
+use std::cell::{Cell, UnsafeCell};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use {violation, EmptyOptionExt, OptionGuard, OptionGuardMut};
+
+
+/// Extension trait providing `steal_copy` on `Cell<Option<T>>` for `T: Copy`.
+pub trait CellOptionExt {
+    type Inner: Copy;
+
+    /// Copy the value out of the cell, providing a guard which writes the (possibly modified) copy
+    /// back when dropped. The cell is never left `None`, so code observing it in the meantime
+    /// always sees the old value. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_copy<'a>(&'a self) -> CopyGuard<'a, Self::Inner>;
+}
+
+
+impl<T: Copy> CellOptionExt for Cell<Option<T>> {
+    type Inner = T;
+
+    #[track_caller]
+    fn steal_copy<'a>(&'a self) -> CopyGuard<'a, T> {
+        let value = match self.get() {
+            Some(value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        };
+
+        CopyGuard { cell: self, value }
+    }
+}
+
+
+/// A copy of the value in a `Cell<Option<T>>`, taken by `CellOptionExt::steal_copy`.
+/// `CopyGuard<T>` dereferences to the copy, and writes it back to the cell when dropped. Since
+/// the value is copied rather than moved, there is no window during which the cell is vacant.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::CellOptionExt;
+/// use std::cell::Cell;
+///
+/// let counter = Cell::new(Some(5));
+///
+/// {
+///     let mut stolen = counter.steal_copy();
+///
+///     *stolen += 1;
+///
+///     // The cell still holds the old value until the guard writes the copy back.
+///     assert_eq!(counter.get(), Some(5));
+/// }
+///
+/// assert_eq!(counter.get(), Some(6));
+/// ```
+pub struct CopyGuard<'a, T: 'a + Copy> {
+    cell: &'a Cell<Option<T>>,
+    value: T,
+}
+
+
+impl<'a, T: Copy> CopyGuard<'a, T> {
+    /// Keep the copy without writing it back, leaving the cell's value untouched.
+    pub fn into_inner(self) -> T {
+        let value = self.value;
+
+        mem::forget(self);
+        value
+    }
+}
+
+
+impl<'a, T: Copy> Drop for CopyGuard<'a, T> {
+    fn drop(&mut self) {
+        self.cell.set(Some(self.value));
+    }
+}
+
+
+impl<'a, T: Copy> Deref for CopyGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+
+impl<'a, T: Copy> DerefMut for CopyGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+
+/// Extension trait providing unchecked steals from an `UnsafeCell<Option<T>>`, as building blocks
+/// for custom synchronization primitives, arenas and the like.
+///
+/// These return the ordinary `OptionGuard` and `OptionGuardMut`, which hold an `&mut Option<T>`
+/// created from the cell's raw pointer. The aliasing rules are therefore exactly those of a
+/// `&mut`: from the moment of the steal until the guard is restored, dropped or consumed, the
+/// guard must be the only way the cell's contents are accessed. No other reference into the cell
+/// may be live or created, and no reads or writes through `UnsafeCell::get` may happen, on any
+/// thread. Enforcing this (with a lock, a flag, or the structure of the surrounding code) is the
+/// caller's job.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::UnsafeCellOptionExt;
+/// use std::cell::UnsafeCell;
+///
+/// /// A single-threaded slot which hands out its value at most once at a time.
+/// struct Slot<T> {
+///     busy: std::cell::Cell<bool>,
+///     value: UnsafeCell<Option<T>>,
+/// }
+///
+/// impl<T> Slot<T> {
+///     fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+///         if self.busy.replace(true) {
+///             return None;
+///         }
+///
+///         // The `busy` flag guarantees this is the only access to the cell until it is reset.
+///         let mut stolen = unsafe { self.value.steal_mut_unchecked() };
+///         let result = f(&mut stolen);
+///
+///         drop(stolen);
+///         self.busy.set(false);
+///         Some(result)
+///     }
+/// }
+///
+/// let slot = Slot { busy: Default::default(), value: UnsafeCell::new(Some(5)) };
+///
+/// assert_eq!(slot.with(|five| { *five += 1; *five }), Some(6));
+/// assert_eq!(slot.value.into_inner(), Some(6));
+/// ```
+pub trait UnsafeCellOptionExt {
+    type Inner;
+
+    /// Take the value out of the cell, providing a guard which panics if the value is not
+    /// returned. Panics on `None`.
+    ///
+    /// # Safety
+    ///
+    /// Until the guard is restored, the guard must be the only access to the cell's contents, as
+    /// described on `UnsafeCellOptionExt`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    unsafe fn steal_unchecked<'a>(&'a self) -> (OptionGuard<'a, Self::Inner>, Self::Inner);
+
+    /// Take the value out of the cell, providing a guard which returns the value unless consumed
+    /// by `OptionGuardMut::into_inner`. Panics on `None`.
+    ///
+    /// # Safety
+    ///
+    /// Until the guard is dropped or consumed, the guard must be the only access to the cell's
+    /// contents, as described on `UnsafeCellOptionExt`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    unsafe fn steal_mut_unchecked<'a>(&'a self) -> OptionGuardMut<'a, Self::Inner>;
+}
+
+
+impl<T> UnsafeCellOptionExt for UnsafeCell<Option<T>> {
+    type Inner = T;
+
+    #[track_caller]
+    unsafe fn steal_unchecked<'a>(&'a self) -> (OptionGuard<'a, T>, T) {
+        (*self.get()).steal()
+    }
+
+    #[track_caller]
+    unsafe fn steal_mut_unchecked<'a>(&'a self) -> OptionGuardMut<'a, T> {
+        (*self.get()).steal_mut()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn into_inner_leaves_value() {
+        let cell = Cell::new(Some(1));
+
+        {
+            let mut stolen = cell.steal_copy();
+            *stolen = 2;
+            assert_eq!(stolen.into_inner(), 2);
+        }
+
+        assert_eq!(cell.get(), Some(1));
+    }
+
+    #[test]
+    fn unchecked_steals() {
+        let cell = UnsafeCell::new(Some(1));
+
+        unsafe {
+            let (guard, one) = cell.steal_unchecked();
+            guard.restore(one + 1);
+
+            *cell.steal_mut_unchecked() += 1;
+        }
+
+        assert_eq!(cell.into_inner(), Some(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn copy_from_none() {
+        let cell: Cell<Option<i32>> = Cell::new(None);
+
+        cell.steal_copy();
+    }
+}
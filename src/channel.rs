@@ -0,0 +1,152 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::mpsc::{Receiver, RecvError, SendError, Sender, TryRecvError};
+
+
+/// Extension trait providing `recv_steal` and `try_recv_steal` on channel receivers.
+pub trait ReceiverStealExt<T> {
+    /// Block until a value is received, providing a guard which sends it back over `back` unless
+    /// consumed by `SendBackGuard::into_inner`. Fails if the sending side has hung up.
+    fn recv_steal(&self, back: &Sender<T>) -> Result<SendBackGuard<T>, RecvError>;
+
+    /// Receive a value if one is waiting right now, providing a guard which sends it back over
+    /// `back` unless consumed by `SendBackGuard::into_inner`.
+    fn try_recv_steal(&self, back: &Sender<T>) -> Result<SendBackGuard<T>, TryRecvError>;
+}
+
+
+impl<T> ReceiverStealExt<T> for Receiver<T> {
+    fn recv_steal(&self, back: &Sender<T>) -> Result<SendBackGuard<T>, RecvError> {
+        self.recv().map(|value| SendBackGuard::new(value, back.clone()))
+    }
+
+
+    fn try_recv_steal(&self, back: &Sender<T>) -> Result<SendBackGuard<T>, TryRecvError> {
+        self.try_recv().map(|value| SendBackGuard::new(value, back.clone()))
+    }
+}
+
+
+/// A value borrowed from the other end of a channel. `SendBackGuard<T>` dereferences to a `T`, and
+/// the inner `T` can be moved out with `SendBackGuard::into_inner`. When dropped, the
+/// `SendBackGuard` sends the value back over its return channel, giving request/response handoffs
+/// the same can't-lose-the-value guarantee as a stolen `Option`. If the return channel has hung
+/// up, there is nowhere for the value to go, and it is dropped.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::ReceiverStealExt;
+/// use std::sync::mpsc;
+/// use std::thread;
+///
+/// let (to_worker, from_owner) = mpsc::channel::<Vec<u8>>();
+/// let (to_owner, from_worker) = mpsc::channel();
+///
+/// let worker = thread::spawn(move || {
+///     // Borrow the buffer; it goes back to the owner when the guard is dropped.
+///     let mut buffer = from_owner.recv_steal(&to_owner).unwrap();
+///     buffer.push(1);
+/// });
+///
+/// to_worker.send(Vec::new()).unwrap();
+/// worker.join().unwrap();
+///
+/// assert_eq!(from_worker.recv().unwrap(), vec![1]);
+/// ```
+pub struct SendBackGuard<T> {
+    value: Option<T>,
+    back: Sender<T>,
+}
+
+
+impl<T> SendBackGuard<T> {
+    /// Wrap a value received from a channel, to be sent back over `back` when the guard is
+    /// dropped.
+    pub fn new(value: T, back: Sender<T>) -> SendBackGuard<T> {
+        SendBackGuard {
+            value: Some(value),
+            back,
+        }
+    }
+
+
+    /// Send the value back right away, handing it back instead if the return channel has hung up.
+    pub fn send_back(mut self) -> Result<(), SendError<T>> {
+        self.back.send(self.value.take().unwrap())
+    }
+
+
+    /// Keep the value and do not send it back.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<T> Drop for SendBackGuard<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            let _ = self.back.send(value);
+        }
+    }
+}
+
+
+impl<T> Deref for SendBackGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<T> DerefMut for SendBackGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+impl<T: fmt::Debug> fmt::Debug for SendBackGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SendBackGuard")
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+
+    #[test]
+    fn sent_back_unless_kept() {
+        let (tx, rx) = mpsc::channel();
+        let (back, returned) = mpsc::channel();
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+
+        drop(rx.recv_steal(&back).unwrap());
+        assert_eq!(rx.try_recv_steal(&back).unwrap().into_inner(), 2);
+
+        assert_eq!(returned.try_recv(), Ok(1));
+        assert!(returned.try_recv().is_err());
+        assert!(rx.try_recv_steal(&back).is_err());
+    }
+
+    #[test]
+    fn send_back_after_hang_up() {
+        let (back, returned) = mpsc::channel();
+        let guard = SendBackGuard::new(1, back);
+
+        drop(returned);
+
+        assert_eq!(guard.send_back(), Err(SendError(1)));
+    }
+}
@@ -0,0 +1,227 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use std::fmt;
+
+use {EmptyOptionExt, OptionGuard, StealError};
+
+
+type Subscriber<T> = Box<dyn FnMut(&T) + Send>;
+
+
+/// A slot holding a live-reloadable configuration value. Stealing from a `ConfigSlot` works just
+/// like `EmptyOptionExt::steal`, except that restoring a value through the resulting
+/// `ConfigGuard` also notifies every registered subscriber of the new value.
+///
+/// Subscribers must be `Send`, so a `ConfigSlot` can be handed to (or shared behind a `Mutex`
+/// with) the thread which reloads it whenever `T` is `Send`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::ConfigSlot;
+/// use std::sync::atomic::{AtomicU32, Ordering};
+/// use std::sync::Arc;
+///
+/// let seen = Arc::new(AtomicU32::new(0));
+///
+/// let mut config = ConfigSlot::new(5);
+///
+/// {
+///     let seen = seen.clone();
+///     config.subscribe(move |value| seen.store(*value, Ordering::SeqCst));
+/// }
+///
+/// {
+///     let (guard, old) = config.steal();
+///
+///     assert_eq!(old, 5);
+///
+///     // Subscribers are run once the new value is in place.
+///     guard.restore(old + 1);
+/// }
+///
+/// assert_eq!(*config.get(), 6);
+/// assert_eq!(seen.load(Ordering::SeqCst), 6);
+/// ```
+pub struct ConfigSlot<T> {
+    value: Option<T>,
+    subscribers: Vec<Subscriber<T>>,
+}
+
+
+impl<T> ConfigSlot<T> {
+    /// Create a new `ConfigSlot` holding an initial configuration value and no subscribers.
+    pub const fn new(value: T) -> ConfigSlot<T> {
+        ConfigSlot {
+            value: Some(value),
+            subscribers: Vec::new(),
+        }
+    }
+
+
+    /// Register a callback to be run with the new value every time a value is restored.
+    pub fn subscribe<F>(&mut self, subscriber: F)
+    where
+        F: FnMut(&T) + Send + 'static,
+    {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+
+    /// Get a reference to the current configuration value. Panics if the value was stolen and
+    /// never restored.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on a vacant slot, which the `strict` feature forbids")
+    )]
+    pub fn get(&self) -> &T {
+        self.value.as_ref().expect("attempted to read a vacant ConfigSlot")
+    }
+
+
+    /// Take the current configuration value out of the slot, providing a guard which panics if a
+    /// value is not restored. Panics if the value was stolen and never restored.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on a vacant slot, which the `strict` feature forbids; use `try_steal`")
+    )]
+    #[track_caller]
+    pub fn steal<'a>(&'a mut self) -> (ConfigGuard<'a, T>, T) {
+        let (guard, value) = self.value.steal();
+
+        let guard = ConfigGuard {
+            guard,
+            subscribers: &mut self.subscribers,
+        };
+
+        (guard, value)
+    }
+
+
+    /// Like `ConfigSlot::steal`, but returning a `StealError` if the slot is vacant instead of
+    /// panicking.
+    #[track_caller]
+    pub fn try_steal<'a>(&'a mut self) -> Result<(ConfigGuard<'a, T>, T), StealError> {
+        let (guard, value) = self.value.try_steal()?;
+
+        let guard = ConfigGuard {
+            guard,
+            subscribers: &mut self.subscribers,
+        };
+
+        Ok((guard, value))
+    }
+}
+
+
+impl<T: fmt::Debug> fmt::Debug for ConfigSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConfigSlot")
+            .field("value", &self.value)
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+
+/// A configuration value stolen from a `ConfigSlot`. Like `OptionGuard`, a `ConfigGuard` panics
+/// if it is dropped without a value being restored.
+pub struct ConfigGuard<'a, T: 'a> {
+    guard: OptionGuard<'a, T>,
+    subscribers: &'a mut Vec<Subscriber<T>>,
+}
+
+
+impl<'a, T> ConfigGuard<'a, T> {
+    /// Restore a value to the `ConfigSlot` and notify all subscribers of it, in the order in which
+    /// they subscribed.
+    pub fn restore(self, value: T) {
+        let slot = self.guard.into_slot();
+        *slot = Some(value);
+
+        let value = slot.as_ref().unwrap();
+
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber(value);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    #[test]
+    fn restore_notifies_in_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut config = ConfigSlot::new("old");
+
+        for id in 0..3 {
+            let log = log.clone();
+            config.subscribe(move |value| log.lock().unwrap().push((id, *value)));
+        }
+
+        {
+            let (guard, old) = config.steal();
+
+            assert_eq!(old, "old");
+            assert!(log.lock().unwrap().is_empty());
+
+            guard.restore("new");
+        }
+
+        assert_eq!(*config.get(), "new");
+        assert_eq!(*log.lock().unwrap(), vec![(0, "new"), (1, "new"), (2, "new")]);
+    }
+
+    #[test]
+    fn reload_from_another_thread() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let config = Arc::new(Mutex::new(ConfigSlot::new(1)));
+
+        {
+            let seen = seen.clone();
+            config.lock().unwrap().subscribe(move |value| seen.lock().unwrap().push(*value));
+        }
+
+        let reloader = {
+            let config = config.clone();
+
+            thread::spawn(move || {
+                let mut config = config.lock().unwrap();
+                let (guard, old) = config.steal();
+
+                guard.restore(old + 1);
+            })
+        };
+
+        reloader.join().unwrap();
+
+        assert_eq!(*config.lock().unwrap().get(), 2);
+        assert_eq!(*seen.lock().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn try_steal_vacant() {
+        use std::mem;
+
+        let mut config = ConfigSlot::new(5);
+
+        let (guard, _) = config.try_steal().unwrap();
+        mem::forget(guard);
+
+        assert_eq!(config.try_steal().err(), Some(StealError));
+    }
+
+    #[test]
+    #[should_panic]
+    fn steal_and_keep() {
+        let mut config = ConfigSlot::new(5);
+
+        let (_, _) = config.steal();
+    }
+}
@@ -0,0 +1,142 @@
+use {OptionGuard, OptionGuardMut};
+
+
+/// A view into an `Option` which is either full or empty, produced by
+/// `EmptyOptionExt::guard_entry`. Like `HashMap::entry`, it lets "fill the option if it is empty,
+/// then steal from it" be written as a single match.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{EmptyOptionExt, GuardEntry};
+/// fn append(log: &mut Option<Vec<&'static str>>, line: &'static str) {
+///     let occupied = match log.guard_entry() {
+///         GuardEntry::Occupied(occupied) => occupied,
+///         GuardEntry::Vacant(vacant) => vacant.insert_with(Vec::new),
+///     };
+///
+///     let (guard, mut lines) = occupied.steal();
+///     lines.push(line);
+///     guard.restore(lines);
+/// }
+///
+/// let mut log = None;
+///
+/// append(&mut log, "one");
+/// append(&mut log, "two");
+///
+/// assert_eq!(log, Some(vec!["one", "two"]));
+/// ```
+pub enum GuardEntry<'a, T: 'a> {
+    /// The `Option` holds a value.
+    Occupied(OccupiedGuard<'a, T>),
+
+    /// The `Option` is `None`.
+    Vacant(VacantGuard<'a, T>),
+}
+
+
+impl<'a, T> GuardEntry<'a, T> {
+    pub(crate) fn new(opt: &'a mut Option<T>) -> GuardEntry<'a, T> {
+        if opt.is_some() {
+            GuardEntry::Occupied(OccupiedGuard { opt })
+        } else {
+            GuardEntry::Vacant(VacantGuard { opt })
+        }
+    }
+
+
+    /// Fill the `Option` with the value computed by `f` if it is empty, and return the occupied
+    /// entry either way.
+    pub fn or_insert_with<F: FnOnce() -> T>(self, f: F) -> OccupiedGuard<'a, T> {
+        match self {
+            GuardEntry::Occupied(occupied) => occupied,
+            GuardEntry::Vacant(vacant) => vacant.insert_with(f),
+        }
+    }
+}
+
+
+/// An `Option` known to hold a value, from `GuardEntry::Occupied`. Unlike the methods of
+/// `EmptyOptionExt`, its steals cannot fail.
+#[derive(Debug)]
+pub struct OccupiedGuard<'a, T: 'a> {
+    opt: &'a mut Option<T>,
+}
+
+
+impl<'a, T> OccupiedGuard<'a, T> {
+    /// A reference to the value in the `Option`.
+    pub fn get(&self) -> &T {
+        self.opt.as_ref().unwrap()
+    }
+
+
+    /// A mutable reference to the value in the `Option`.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.opt.as_mut().unwrap()
+    }
+
+
+    /// Convert the entry into a mutable reference to the value, bound to the `Option`'s lifetime.
+    pub fn into_mut(self) -> &'a mut T {
+        self.opt.as_mut().unwrap()
+    }
+
+
+    /// Take the value, as `EmptyOptionExt::steal` does.
+    #[track_caller]
+    pub fn steal(self) -> (OptionGuard<'a, T>, T) {
+        let value = self.opt.take().unwrap();
+
+        (OptionGuard::new(self.opt), value)
+    }
+
+
+    /// Take the value, as `EmptyOptionExt::steal_mut` does.
+    #[track_caller]
+    pub fn steal_mut(self) -> OptionGuardMut<'a, T> {
+        let value = self.opt.take().unwrap();
+
+        unsafe { OptionGuardMut::from_parts(self.opt, value) }
+    }
+}
+
+
+/// An `Option` known to be `None`, from `GuardEntry::Vacant`.
+#[derive(Debug)]
+pub struct VacantGuard<'a, T: 'a> {
+    opt: &'a mut Option<T>,
+}
+
+
+impl<'a, T> VacantGuard<'a, T> {
+    /// Fill the `Option` with a value, returning the now occupied entry.
+    pub fn insert(self, value: T) -> OccupiedGuard<'a, T> {
+        *self.opt = Some(value);
+
+        OccupiedGuard { opt: self.opt }
+    }
+
+
+    /// Fill the `Option` with the value computed by `f`, returning the now occupied entry.
+    pub fn insert_with<F: FnOnce() -> T>(self, f: F) -> OccupiedGuard<'a, T> {
+        self.insert(f())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use EmptyOptionExt;
+
+    #[test]
+    fn vacant_then_steal_mut() {
+        let mut thing = None;
+
+        *thing.guard_entry().or_insert_with(|| 1).steal_mut() += 1;
+        *thing.guard_entry().or_insert_with(|| 10).get_mut() += 1;
+
+        assert_eq!(thing, Some(3));
+    }
+}
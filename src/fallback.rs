@@ -0,0 +1,109 @@
+use std::fmt;
+
+
+/// An option which has had its value taken by `EmptyOptionExt::steal_with_fallback`. Restore the
+/// stolen value (or a replacement) with `FallbackGuard::restore`; if the guard is dropped without
+/// one, the fallback value it holds is written back instead, so the option is never left `None`,
+/// not even by an early return, a `?` or a panic.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// fn parse_into(dest: &mut Option<Vec<u32>>, input: &str) -> Result<(), std::num::ParseIntError> {
+///     let (guard, mut numbers) = dest.steal_with_fallback(Vec::new());
+///
+///     for word in input.split_whitespace() {
+///         numbers.push(word.parse()?);
+///     }
+///
+///     guard.restore(numbers);
+///     Ok(())
+/// }
+///
+/// let mut numbers = Some(vec![1]);
+///
+/// assert!(parse_into(&mut numbers, "2 3").is_ok());
+/// assert_eq!(numbers, Some(vec![1, 2, 3]));
+///
+/// assert!(parse_into(&mut numbers, "4 five").is_err());
+/// assert_eq!(numbers, Some(vec![]));
+/// ```
+pub struct FallbackGuard<'a, T: 'a> {
+    opt: &'a mut Option<T>,
+    fallback: Option<T>,
+}
+
+
+impl<'a, T> FallbackGuard<'a, T> {
+    pub(crate) fn new(opt: &'a mut Option<T>, fallback: T) -> FallbackGuard<'a, T> {
+        FallbackGuard {
+            opt,
+            fallback: Some(fallback),
+        }
+    }
+
+
+    /// Restore a stolen value to the `Option`, dropping the fallback.
+    pub fn restore(mut self, obj: T) {
+        self.fallback = None;
+        *self.opt = Some(obj);
+    }
+
+
+    /// The value which will be written back if the guard is dropped without a restore.
+    pub fn fallback(&self) -> &T {
+        self.fallback.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for FallbackGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(fallback) = self.fallback.take() {
+            *self.opt = Some(fallback);
+        }
+    }
+}
+
+
+impl<'a, T: fmt::Debug> fmt::Debug for FallbackGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FallbackGuard")
+            .field("fallback", &self.fallback)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use EmptyOptionExt;
+
+    #[test]
+    fn fallback_survives_panic() {
+        let mut thing = Some(5);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (guard, _) = thing.steal_with_fallback(-1);
+
+            assert_eq!(*guard.fallback(), -1);
+            panic!("early exit");
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(thing, Some(-1));
+    }
+
+    #[test]
+    fn restore_drops_fallback() {
+        let mut thing = Some(5);
+
+        let (guard, five) = thing.steal_with_fallback(-1);
+        guard.restore(five + 1);
+
+        assert_eq!(thing, Some(6));
+    }
+}
@@ -0,0 +1,236 @@
+//! A C-facing API for parking values in slots owned by the Rust side.
+//!
+//! Slots are opaque `EmptyOptionSlot` pointers holding `void *` values. None of these functions
+//! ever panic or unwind into C: contract violations (stealing from an empty slot, restoring to a
+//! slot which was never stolen from, destroying a slot whose value is still stolen, or passing a
+//! null pointer) are instead reported through the callback registered with
+//! `empty_option_set_violation_callback`, and the offending call returns `false`.
+
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::Mutex;
+
+use poison;
+
+
+/// An opaque slot holding a `void *` value on behalf of a C host.
+#[derive(Debug)]
+pub struct EmptyOptionSlot {
+    value: Option<*mut c_void>,
+    stolen: bool,
+}
+
+
+/// The kinds of contract violation reported to the violation callback.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EmptyOptionViolation {
+    /// A null pointer was passed where a slot or an out-pointer was required.
+    NullPointer = 1,
+
+    /// A steal was attempted from a slot which was empty or whose value was already stolen.
+    StealFromNone = 2,
+
+    /// A value was restored to a slot whose value was not stolen.
+    RestoreWithoutSteal = 3,
+
+    /// A slot was destroyed while its value was still stolen. The slot is not destroyed.
+    DestroyWhileStolen = 4,
+}
+
+
+/// A callback invoked with the kind of violation and the slot involved (which may be null).
+pub type EmptyOptionViolationCallback =
+    extern "C" fn(violation: EmptyOptionViolation, slot: *const EmptyOptionSlot);
+
+
+static VIOLATION_CALLBACK: Mutex<Option<EmptyOptionViolationCallback>> = Mutex::new(None);
+
+
+fn violation(violation: EmptyOptionViolation, slot: *const EmptyOptionSlot) -> bool {
+    let callback = *poison::lock(&VIOLATION_CALLBACK);
+
+    if let Some(callback) = callback {
+        callback(violation, slot);
+    }
+
+    false
+}
+
+
+/// Register a callback to be invoked on every contract violation, replacing any previous one.
+/// Passing null unregisters the callback, and violations are then only reported through return
+/// values.
+#[no_mangle]
+pub extern "C" fn empty_option_set_violation_callback(
+    callback: Option<EmptyOptionViolationCallback>,
+) {
+    *poison::lock(&VIOLATION_CALLBACK) = callback;
+}
+
+
+/// Create a slot holding `value`. The slot must eventually be freed with
+/// `empty_option_slot_destroy`.
+#[no_mangle]
+pub extern "C" fn empty_option_slot_new(value: *mut c_void) -> *mut EmptyOptionSlot {
+    Box::into_raw(Box::new(EmptyOptionSlot {
+        value: Some(value),
+        stolen: false,
+    }))
+}
+
+
+/// Steal the value out of `slot`, writing it to `out`. Returns `false` (and reports a violation)
+/// if the slot is empty or its value is already stolen.
+///
+/// # Safety
+///
+/// `slot` must be null or a live pointer returned by `empty_option_slot_new`, and `out` must be
+/// null or valid for writes. The slot must not be used concurrently from another thread.
+#[no_mangle]
+pub unsafe extern "C" fn empty_option_slot_steal(
+    slot: *mut EmptyOptionSlot,
+    out: *mut *mut c_void,
+) -> bool {
+    if slot.is_null() || out.is_null() {
+        return violation(EmptyOptionViolation::NullPointer, slot);
+    }
+
+    let slot_ref = &mut *slot;
+
+    match slot_ref.value.take() {
+        Some(value) => {
+            slot_ref.stolen = true;
+            *out = value;
+            true
+        }
+        None => violation(EmptyOptionViolation::StealFromNone, slot),
+    }
+}
+
+
+/// Restore a value to a slot whose value was stolen. Returns `false` (and reports a violation) if
+/// the slot's value was not stolen.
+///
+/// # Safety
+///
+/// `slot` must be null or a live pointer returned by `empty_option_slot_new`. The slot must not be
+/// used concurrently from another thread.
+#[no_mangle]
+pub unsafe extern "C" fn empty_option_slot_restore(
+    slot: *mut EmptyOptionSlot,
+    value: *mut c_void,
+) -> bool {
+    if slot.is_null() {
+        return violation(EmptyOptionViolation::NullPointer, slot);
+    }
+
+    let slot_ref = &mut *slot;
+
+    if !slot_ref.stolen {
+        return violation(EmptyOptionViolation::RestoreWithoutSteal, slot);
+    }
+
+    slot_ref.value = Some(value);
+    slot_ref.stolen = false;
+
+    true
+}
+
+
+/// Returns `true` if `slot` holds a value which is not currently stolen.
+///
+/// # Safety
+///
+/// `slot` must be null or a live pointer returned by `empty_option_slot_new`.
+#[no_mangle]
+pub unsafe extern "C" fn empty_option_slot_is_occupied(slot: *const EmptyOptionSlot) -> bool {
+    if slot.is_null() {
+        return violation(EmptyOptionViolation::NullPointer, slot);
+    }
+
+    (*slot).value.is_some()
+}
+
+
+/// Destroy `slot`, writing the value it holds to `out` (if `out` is not null). Returns `false`
+/// (and reports a violation) without destroying the slot if its value is still stolen.
+///
+/// # Safety
+///
+/// `slot` must be null or a live pointer returned by `empty_option_slot_new`, and `out` must be
+/// null or valid for writes. Once this returns `true`, `slot` must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn empty_option_slot_destroy(
+    slot: *mut EmptyOptionSlot,
+    out: *mut *mut c_void,
+) -> bool {
+    if slot.is_null() {
+        return violation(EmptyOptionViolation::NullPointer, slot);
+    }
+
+    if (*slot).stolen {
+        return violation(EmptyOptionViolation::DestroyWhileStolen, slot);
+    }
+
+    let slot = Box::from_raw(slot);
+
+    if !out.is_null() {
+        *out = slot.value.unwrap_or(ptr::null_mut());
+    }
+
+    true
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SEEN: Mutex<Vec<EmptyOptionViolation>> = Mutex::new(Vec::new());
+
+    extern "C" fn record(violation: EmptyOptionViolation, _: *const EmptyOptionSlot) {
+        SEEN.lock().unwrap().push(violation);
+    }
+
+    #[test]
+    fn steal_restore_destroy() {
+        empty_option_set_violation_callback(Some(record));
+
+        let mut five = 5i32;
+        let five_ptr = &mut five as *mut i32 as *mut c_void;
+
+        unsafe {
+            let slot = empty_option_slot_new(five_ptr);
+            let mut out = ptr::null_mut();
+
+            assert!(empty_option_slot_steal(slot, &mut out));
+            assert_eq!(out, five_ptr);
+
+            assert!(!empty_option_slot_steal(slot, &mut out));
+            assert!(!empty_option_slot_destroy(slot, ptr::null_mut()));
+
+            assert!(empty_option_slot_restore(slot, out));
+            assert!(!empty_option_slot_restore(slot, out));
+            assert!(empty_option_slot_is_occupied(slot));
+
+            assert!(!empty_option_slot_steal(ptr::null_mut(), &mut out));
+
+            out = ptr::null_mut();
+            assert!(empty_option_slot_destroy(slot, &mut out));
+            assert_eq!(out, five_ptr);
+        }
+
+        empty_option_set_violation_callback(None);
+
+        assert_eq!(
+            *SEEN.lock().unwrap(),
+            vec![
+                EmptyOptionViolation::StealFromNone,
+                EmptyOptionViolation::DestroyWhileStolen,
+                EmptyOptionViolation::RestoreWithoutSteal,
+                EmptyOptionViolation::NullPointer,
+            ]
+        );
+    }
+}
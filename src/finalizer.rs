@@ -0,0 +1,218 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "std")]
+use std::thread;
+
+use OptionGuard;
+
+
+/// A collection of deferred restore obligations, run together at a single, controlled point.
+///
+/// Instead of restoring stolen values wherever their guards happen to go out of scope, guards
+/// and their replacement values can be handed to a `Finalizer`, which restores all of them in
+/// registration order when `Finalizer::flush` is called (for example, at the end of a frame or a
+/// transaction). Dropping a `Finalizer` flushes any obligations which are still pending.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{EmptyOptionExt, Finalizer};
+/// let mut a = Some(1);
+/// let mut b = Some(2);
+///
+/// {
+///     let mut finalizer = Finalizer::new();
+///
+///     let (guard, one) = a.steal();
+///     finalizer.defer_restore(guard, one + 10);
+///
+///     let (guard, two) = b.steal();
+///     finalizer.defer_restore(guard, two + 10);
+///
+///     assert_eq!(finalizer.len(), 2);
+///     assert_eq!(finalizer.flush(), 2);
+/// }
+///
+/// assert_eq!(a, Some(11));
+/// assert_eq!(b, Some(12));
+/// ```
+pub struct Finalizer<'a> {
+    pending: Vec<Box<dyn FnOnce() + 'a>>,
+}
+
+
+impl<'a> Finalizer<'a> {
+    /// Create a `Finalizer` with no pending obligations.
+    pub const fn new() -> Finalizer<'a> {
+        Finalizer {
+            pending: Vec::new(),
+        }
+    }
+
+
+    /// Register an arbitrary closure to be run on the next flush.
+    pub fn defer<F>(&mut self, f: F)
+    where
+        F: FnOnce() + 'a,
+    {
+        self.pending.push(Box::new(f));
+    }
+
+
+    /// Register a value to be restored through an `OptionGuard` on the next flush.
+    pub fn defer_restore<T: 'a>(&mut self, guard: OptionGuard<'a, T>, value: T) {
+        self.defer(move || guard.restore(value));
+    }
+
+
+    /// Hold on to a guard which restores on drop (such as an `OptionGuardMut` or a `PoolGuard`)
+    /// and drop it on the next flush.
+    pub fn defer_drop<G: 'a>(&mut self, guard: G) {
+        self.defer(move || drop(guard));
+    }
+
+
+    /// Run every pending obligation, in the order in which they were registered, and return how
+    /// many were run.
+    ///
+    /// If an obligation panics, the rest are still run - so every deferred restore still happens -
+    /// and the first panic is resumed once they have all finished. Without the `std` feature
+    /// panics cannot be caught, and a panicking obligation ends the flush; the obligations after
+    /// it are dropped without being run, and a `defer_restore` among them panics in turn.
+    pub fn flush(&mut self) -> usize {
+        let pending = self.pending.len();
+
+        #[cfg(feature = "std")]
+        {
+            let mut first_panic = None;
+
+            for obligation in self.pending.drain(..) {
+                if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(obligation)) {
+                    first_panic.get_or_insert(payload);
+                }
+            }
+
+            if let Some(payload) = first_panic {
+                panic::resume_unwind(payload);
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        for obligation in self.pending.drain(..) {
+            obligation();
+        }
+
+        pending
+    }
+
+
+    /// The number of obligations waiting for the next flush.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+
+    /// Returns `true` if there are no obligations waiting for the next flush.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+
+impl<'a> Default for Finalizer<'a> {
+    fn default() -> Finalizer<'a> {
+        Finalizer::new()
+    }
+}
+
+
+impl<'a> Drop for Finalizer<'a> {
+    fn drop(&mut self) {
+        // Resuming an obligation's panic while already unwinding would abort, so drop it instead.
+        #[cfg(feature = "std")]
+        if thread::panicking() {
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| self.flush()));
+            return;
+        }
+
+        self.flush();
+    }
+}
+
+
+impl<'a> fmt::Debug for Finalizer<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Finalizer")
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+
+    use EmptyOptionExt;
+
+    #[test]
+    fn flush_in_registration_order() {
+        let log = RefCell::new(Vec::new());
+        let mut finalizer = Finalizer::new();
+
+        for i in 0..3 {
+            let log = &log;
+            finalizer.defer(move || log.borrow_mut().push(i));
+        }
+
+        assert!(log.borrow().is_empty());
+        assert_eq!(finalizer.flush(), 3);
+        assert_eq!(*log.borrow(), vec![0, 1, 2]);
+        assert!(finalizer.is_empty());
+    }
+
+    #[test]
+    fn drop_flushes_pending() {
+        let mut a = Some(5);
+        let mut b = Some(6);
+
+        {
+            let mut finalizer = Finalizer::new();
+
+            let (guard, five) = a.steal();
+            finalizer.defer_restore(guard, five * 2);
+
+            let mut six = b.steal_mut();
+            *six += 1;
+            finalizer.defer_drop(six);
+        }
+
+        assert_eq!(a, Some(10));
+        assert_eq!(b, Some(7));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn panicking_obligation_runs_the_rest() {
+        let mut thing = Some(1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut finalizer = Finalizer::new();
+
+            finalizer.defer(|| panic!("failed to commit"));
+
+            let (guard, one) = thing.steal();
+            finalizer.defer_restore(guard, one + 1);
+
+            finalizer.flush();
+        }));
+
+        assert_eq!(*result.unwrap_err().downcast_ref::<&str>().unwrap(), "failed to commit");
+        assert_eq!(thing, Some(2));
+    }
+}
@@ -0,0 +1,158 @@
+use {violation, OptionGuard};
+
+
+/// Extension trait providing `steal_flatten` on nested options: on `Option<Option<T>>`s, and on
+/// the `Option<&mut Option<T>>`s which lookups like `HashMap::get_mut` return.
+pub trait FlattenOptionExt<'a> {
+    type Inner;
+
+    /// Take the value out of both layers of a nested option, providing a guard which panics if
+    /// the value is not returned. Panics, leaving both layers untouched, if either is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_flatten(self) -> (FlattenGuard<'a, Self::Inner>, Self::Inner);
+}
+
+
+impl<'a, T> FlattenOptionExt<'a> for &'a mut Option<Option<T>> {
+    type Inner = T;
+
+    #[track_caller]
+    fn steal_flatten(self) -> (FlattenGuard<'a, T>, T) {
+        let value = match self.take() {
+            Some(Some(value)) => value,
+            taken => {
+                *self = taken;
+                violation::steal_from_none::<T, _>(self)
+            }
+        };
+
+        let guard = FlattenGuard {
+            layers: Layers::Outer(OptionGuard::new(self)),
+        };
+
+        (guard, value)
+    }
+}
+
+
+impl<'a, T> FlattenOptionExt<'a> for Option<&'a mut Option<T>> {
+    type Inner = T;
+
+    #[track_caller]
+    fn steal_flatten(self) -> (FlattenGuard<'a, T>, T) {
+        let inner = match self {
+            Some(inner) if inner.is_some() => inner,
+            Some(inner) => violation::steal_from_none::<T, _>(inner),
+            None => violation::steal_from_none::<T, _>(&self),
+        };
+
+        let value = inner.take().unwrap();
+
+        let guard = FlattenGuard {
+            layers: Layers::Inner(OptionGuard::new(inner)),
+        };
+
+        (guard, value)
+    }
+}
+
+
+/// The layer of a nested option which a `FlattenGuard` emptied.
+enum Layers<'a, T: 'a> {
+    /// The outer layer of an `Option<Option<T>>`.
+    Outer(OptionGuard<'a, Option<T>>),
+
+    /// The `Option<T>` behind an `Option<&mut Option<T>>`.
+    Inner(OptionGuard<'a, T>),
+}
+
+
+/// A nested option which has had its value taken by `FlattenOptionExt::steal_flatten`. An
+/// `Option<Option<T>>` is left with its outer layer `None`; an `Option<&mut Option<T>>` is left
+/// with the `Option<T>` it refers to `None`. Like an `OptionGuard`, the guard panics when dropped
+/// unless the value is restored, which `FlattenGuard::restore` does by refilling the emptied
+/// layer.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::FlattenOptionExt;
+/// // `None` means not looked up yet; `Some(None)` means looked up, but missing.
+/// let mut nickname: Option<Option<String>> = Some(Some(String::from("ally")));
+///
+/// let (guard, ally) = nickname.steal_flatten();
+/// guard.restore(ally.to_uppercase());
+///
+/// assert_eq!(nickname, Some(Some(String::from("ALLY"))));
+/// ```
+pub struct FlattenGuard<'a, T: 'a> {
+    layers: Layers<'a, T>,
+}
+
+
+impl<'a, T> FlattenGuard<'a, T> {
+    /// Restore a stolen value, so that both layers are `Some` again.
+    pub fn restore(self, obj: T) {
+        match self.layers {
+            Layers::Outer(guard) => guard.restore(Some(obj)),
+            Layers::Inner(guard) => guard.restore(obj),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn steal_and_restore() {
+        let mut nested = Some(Some(1));
+
+        let (guard, one) = nested.steal_flatten();
+        guard.restore(one + 1);
+        assert_eq!(nested, Some(Some(2)));
+    }
+
+    #[test]
+    fn inner_none_is_untouched() {
+        let mut nested: Option<Option<i32>> = Some(None);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            nested.steal_flatten();
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(nested, Some(None));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn steal_through_get_mut() {
+        use std::collections::HashMap;
+
+        let mut cache: HashMap<&str, Option<String>> = HashMap::new();
+        cache.insert("hit", Some(String::from("value")));
+        cache.insert("miss", None);
+
+        let (guard, value) = cache.get_mut("hit").steal_flatten();
+        guard.restore(value + "!");
+
+        assert_eq!(cache["hit"].as_deref(), Some("value!"));
+
+        for key in ["miss", "absent"] {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                cache.get_mut(key).steal_flatten();
+            }));
+
+            assert!(result.is_err());
+        }
+
+        assert_eq!(cache["miss"], None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::future::Future;
+use std::ops::DerefMut;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+
+/// A future which runs an inner future while holding a guard, and drops the guard (returning the
+/// value to wherever it came from) as soon as the inner future completes or is cancelled.
+///
+/// `Guarded` pairs a guard which restores on drop, such as an `OptionGuardMut` or an
+/// `OwnedAsyncSlotGuard`, with any `Future` - an `async` block included. The slot stays stolen for
+/// exactly as long as the future runs, so dropping a `Guarded` before it completes - for instance,
+/// when the task running it is cancelled - still puts the value back.
+///
+/// The inner future cannot borrow the guarded value, since the guard lives alongside it; use
+/// `Guarded::with` to build the future from the value instead, cloning out whatever it needs.
+///
+/// # Examples
+///
+/// ```edition2018
+/// # use empty_option::{EmptyOptionExt, Guarded};
+/// use std::future::Future;
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+///
+/// // While the token is stolen, no other request may start.
+/// let mut token = Some(String::from("secret"));
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// {
+///     let request = Guarded::with(token.steal_mut(), |token| {
+///         let header = format!("Bearer {}", token);
+///
+///         async move {
+///             // Wait for a response which never comes.
+///             std::future::pending::<()>().await;
+///             header
+///         }
+///     });
+///
+///     let mut request = pin!(request);
+///     assert!(request.as_mut().poll(&mut cx).is_pending());
+///
+///     // Cancel the request by dropping it before it completes.
+/// }
+///
+/// // The token was put back anyway.
+/// assert_eq!(token.as_deref(), Some("secret"));
+///
+/// {
+///     let request = pin!(Guarded::new(token.steal_mut(), async { 200 }));
+///     assert_eq!(request.poll(&mut cx), Poll::Ready(200));
+/// }
+///
+/// assert!(token.is_some());
+/// ```
+pub struct Guarded<G, F> {
+    // Declared before `guard`, so that a cancelled future is dropped before the value goes back.
+    future: F,
+    guard: Option<G>,
+}
+
+
+impl<G, F: Future> Guarded<G, F> {
+    /// Create a future which runs `future` while holding `guard`, and drops the guard once
+    /// `future` completes.
+    pub fn new(guard: G, future: F) -> Guarded<G, F> {
+        Guarded {
+            future,
+            guard: Some(guard),
+        }
+    }
+
+
+    /// Like `Guarded::new`, but building the future with `make` from the value `guard` holds.
+    pub fn with<M>(mut guard: G, make: M) -> Guarded<G, F>
+    where
+        G: DerefMut,
+        M: FnOnce(&mut G::Target) -> F,
+    {
+        let future = make(&mut guard);
+
+        Guarded::new(guard, future)
+    }
+
+
+    /// Give up on the future and take back the guard, if the future has not yet completed.
+    pub fn into_guard(self) -> Option<G> {
+        self.guard
+    }
+}
+
+
+impl<G, F: Future> Future for Guarded<G, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<F::Output> {
+        // The future is never moved out of the `Guarded`, so it stays pinned; the guard is never
+        // pinned at all.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        if this.guard.is_none() {
+            panic!("`Guarded` polled after completion");
+        }
+
+        let output = match unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx) {
+            Poll::Ready(output) => output,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        this.guard = None;
+
+        Poll::Ready(output)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::future;
+
+    use test_util::{block_on, poll_once};
+    use EmptyOptionExt;
+
+    #[test]
+    fn restore_on_completion() {
+        let mut thing = Some(1);
+
+        let output = block_on(Guarded::with(thing.steal_mut(), |value| {
+            *value += 1;
+            let doubled = *value * 2;
+
+            future::ready(doubled * 10)
+        }));
+
+        assert_eq!(output, 40);
+        assert_eq!(thing, Some(2));
+    }
+
+    #[test]
+    fn restore_on_cancel() {
+        let mut thing = Some(0);
+
+        {
+            let mut stolen = thing.steal_mut();
+            *stolen += 1;
+
+            let mut future = Box::pin(Guarded::new(stolen, future::pending::<()>()));
+
+            assert!(poll_once(&mut future).is_pending());
+        }
+
+        assert_eq!(thing, Some(1));
+    }
+
+    #[test]
+    fn give_up_before_polling() {
+        let mut thing = Some(0);
+
+        let future = Guarded::new(thing.steal_mut(), future::ready(()));
+        *future.into_guard().unwrap() = 5;
+
+        assert_eq!(thing, Some(5));
+    }
+}
@@ -0,0 +1,200 @@
+use std::array;
+use std::fmt;
+
+use {EmptyOptionExt, OptionGuard};
+
+
+/// A fixed-capacity set of up to `N` `OptionGuard`s, stored inline without any heap allocation.
+///
+/// Values stolen through a `GuardSet` are restored together, in the order in which they were
+/// stolen, with `GuardSet::restore_all`. If the `GuardSet` is dropped while still holding guards,
+/// it panics just like a leaked `OptionGuard` would.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::GuardSet;
+/// let mut a = Some(1);
+/// let mut b = Some(2);
+///
+/// {
+///     let mut set = GuardSet::<_, 2>::new();
+///
+///     let one = set.steal(&mut a);
+///     let two = set.steal(&mut b);
+///
+///     assert!(set.is_full());
+///
+///     // Restored in the order the values were stolen: `a` first, then `b`.
+///     set.restore_all(vec![two, one]);
+/// }
+///
+/// assert_eq!(a, Some(2));
+/// assert_eq!(b, Some(1));
+/// ```
+pub struct GuardSet<'a, T: 'a, const N: usize> {
+    guards: [Option<OptionGuard<'a, T>>; N],
+    len: usize,
+}
+
+
+impl<'a, T, const N: usize> GuardSet<'a, T, N> {
+    /// Create an empty `GuardSet`.
+    pub fn new() -> GuardSet<'a, T, N> {
+        GuardSet {
+            guards: array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+
+
+    /// Add a guard to the set. If the set is full, the guard is handed back.
+    pub fn push(&mut self, guard: OptionGuard<'a, T>) -> Result<(), OptionGuard<'a, T>> {
+        if self.is_full() {
+            return Err(guard);
+        }
+
+        self.guards[self.len] = Some(guard);
+        self.len += 1;
+
+        Ok(())
+    }
+
+
+    /// Steal a value from an option, keeping its guard in the set. Panics if the set is full (in
+    /// which case the option is left untouched) or if the option is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None` or a full set, which `strict` forbids")
+    )]
+    #[track_caller]
+    pub fn steal(&mut self, opt: &'a mut Option<T>) -> T {
+        assert!(!self.is_full(), "attempted to steal into a full GuardSet");
+
+        let (guard, value) = opt.steal();
+
+        if self.push(guard).is_err() {
+            unreachable!();
+        }
+
+        value
+    }
+
+
+    /// Restore values to every guarded option, pairing the values with the guards in the order in
+    /// which the guards were added. Any values left over are dropped; if there are too few values,
+    /// the set panics as though it had been leaked.
+    pub fn restore_all<I>(mut self, values: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut values = values.into_iter();
+
+        for slot in self.guards[..self.len].iter_mut() {
+            match values.next() {
+                Some(value) => slot.take().unwrap().restore(value),
+                None => return,
+            }
+        }
+
+        self.len = 0;
+    }
+
+
+    /// The number of guards currently held.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+
+    /// Returns `true` if the set holds no guards.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+
+    /// Returns `true` if the set holds `N` guards and can accept no more.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+
+    /// The maximum number of guards the set can hold.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+
+impl<'a, T, const N: usize> Default for GuardSet<'a, T, N> {
+    fn default() -> GuardSet<'a, T, N> {
+        GuardSet::new()
+    }
+}
+
+
+impl<'a, T, const N: usize> Drop for GuardSet<'a, T, N> {
+    fn drop(&mut self) {
+        let mut leaked = self.guards.iter_mut().filter_map(Option::take);
+
+        if let Some(first) = leaked.next() {
+            // Only the first leaked guard gets to panic; a second panic would abort.
+            for guard in leaked {
+                guard.into_slot();
+            }
+
+            drop(first);
+        }
+    }
+}
+
+
+impl<'a, T, const N: usize> fmt::Debug for GuardSet<'a, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GuardSet")
+            .field("len", &self.len)
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_until_full() {
+        let mut slots = [Some(0), Some(1), Some(2)];
+
+        {
+            let mut set = GuardSet::<_, 2>::new();
+            let mut iter = slots.iter_mut();
+
+            assert_eq!(set.steal(iter.next().unwrap()), 0);
+            assert_eq!(set.steal(iter.next().unwrap()), 1);
+
+            let (guard, two) = iter.next().unwrap().steal();
+            let guard = set.push(guard).unwrap_err();
+            guard.restore(two);
+
+            set.restore_all(vec![10, 11]);
+        }
+
+        assert_eq!(slots, [Some(10), Some(11), Some(2)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_few_values() {
+        let mut a = Some(1);
+        let mut b = Some(2);
+
+        let mut set = GuardSet::<_, 2>::new();
+
+        set.steal(&mut a);
+        set.steal(&mut b);
+
+        set.restore_all(vec![1]);
+    }
+}
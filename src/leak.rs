@@ -0,0 +1,119 @@
+use {violation, GuardLeaked};
+
+
+/// What an `OptionGuard` does when it is dropped without its value being restored, chosen at
+/// steal time with `EmptyOptionExt::steal_with_policy`. The policy is a type parameter of the
+/// guard, so choosing one costs nothing at runtime.
+///
+/// `Panic` is the default, and the policy of every plain `steal`. Implement `OnLeak` for a type
+/// of your own to report leaks some other way.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{EmptyOptionExt, RestoreDefault};
+/// let mut retries = Some(3);
+///
+/// {
+///     let (_guard, _retries) = retries.steal_with_policy::<RestoreDefault>();
+///
+///     // Bail out early without restoring anything.
+/// }
+///
+/// assert_eq!(retries, Some(0));
+/// ```
+pub trait OnLeak<T> {
+    /// Deal with `slot`, whose value was never restored. `leak` describes the value, the slot, and
+    /// where and why the value was stolen.
+    fn leaked(slot: &mut Option<T>, leak: GuardLeaked);
+}
+
+
+/// Panic with a `GuardLeaked` payload. If the guard is dropped while already unwinding from
+/// another panic, this aborts the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Panic;
+
+
+impl<T> OnLeak<T> for Panic {
+    fn leaked(_: &mut Option<T>, leak: GuardLeaked) {
+        violation::raise(leak);
+    }
+}
+
+
+/// Print the `GuardLeaked` diagnostic to standard error and abort the process, without ever
+/// unwinding out of the guard's `Drop`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Abort;
+
+
+impl<T> OnLeak<T> for Abort {
+    fn leaked(_: &mut Option<T>, leak: GuardLeaked) {
+        violation::abort(&leak);
+    }
+}
+
+
+/// Quietly refill the slot with `T::default()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct RestoreDefault;
+
+
+impl<T: Default> OnLeak<T> for RestoreDefault {
+    fn leaked(slot: &mut Option<T>, _: GuardLeaked) {
+        *slot = Some(T::default());
+    }
+}
+
+
+/// Print the `GuardLeaked` diagnostic to standard error and carry on, leaving the slot `None`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct LogAndLeaveNone;
+
+
+#[cfg(feature = "std")]
+impl<T> OnLeak<T> for LogAndLeaveNone {
+    fn leaked(_: &mut Option<T>, leak: GuardLeaked) {
+        eprintln!("warning: {}", leak);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use EmptyOptionExt;
+
+    #[test]
+    fn quiet_policies() {
+        let mut counter = Some(5);
+
+        drop(counter.steal_with_policy::<RestoreDefault>());
+        assert_eq!(counter, Some(0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn log_and_leave_none() {
+        let mut counter = Some(5);
+
+        drop(counter.steal_with_policy::<LogAndLeaveNone>());
+        assert_eq!(counter, None);
+    }
+
+    #[test]
+    fn restored_guards_ignore_policy() {
+        let mut counter = Some(5);
+
+        let (guard, five) = counter.steal_with_policy::<Abort>();
+        guard.restore(five + 1);
+
+        let (guard, six) = counter.steal_strict();
+        guard.restore(six + 1);
+
+        assert_eq!(counter, Some(7));
+    }
+}
@@ -0,0 +1,362 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::task::{Wake, Waker};
+use std::time::{Duration, Instant};
+
+use poison;
+use timer::Timer;
+
+
+struct Lease<T> {
+    id: u64,
+    timer: Option<Arc<Timer>>,
+    fallback: T,
+}
+
+
+struct State<T> {
+    value: Option<T>,
+    lease: Option<Lease<T>>,
+    next_id: u64,
+}
+
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+}
+
+
+impl<T> Inner<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        poison::lock(&self.state)
+    }
+}
+
+
+/// Take a lease's deadline off the timer queue. The slot's lock must not be held, since the timer
+/// thread takes it to expire leases.
+fn cancel(timer: Option<Arc<Timer>>) {
+    if let Some(timer) = timer {
+        timer.cancel();
+    }
+}
+
+
+/// Woken by the shared timer thread once lease `id`'s deadline has passed, installing the fallback
+/// if the lease is still outstanding.
+struct Expiry<T> {
+    inner: Weak<Inner<T>>,
+    id: u64,
+}
+
+
+impl<T> Wake for Expiry<T> {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let inner = match self.inner.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let mut state = inner.lock();
+
+        if state.lease.as_ref().map(|lease| lease.id) == Some(self.id) {
+            // The timer has already left the queue, so there is nothing to cancel.
+            let lease = state.lease.take().unwrap();
+            state.value = Some(lease.fallback);
+        }
+    }
+}
+
+
+/// A slot whose value is lent out for a limited time. Every lease carries a deadline and a
+/// fallback value; if the lease is still outstanding when its deadline passes, the crate's shared
+/// timer thread installs the fallback, so the slot never stays vacant indefinitely even if the task
+/// holding the lease hangs.
+///
+/// A value returned after its lease has expired is discarded in favour of the fallback (or handed
+/// back, with `LeaseGuard::release`).
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::LeaseSlot;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let slot = LeaseSlot::new(String::from("primary"));
+///
+/// {
+///     let lease = slot.lease(Duration::from_millis(10), String::from("fallback")).unwrap();
+///
+///     assert_eq!(*lease, "primary");
+///
+///     // Hang on to the value for too long...
+///     thread::sleep(Duration::from_millis(50));
+///
+///     assert!(lease.is_expired());
+///     assert_eq!(lease.release(), Err(String::from("primary")));
+/// }
+///
+/// assert_eq!(slot.get(), Some(String::from("fallback")));
+/// ```
+pub struct LeaseSlot<T> {
+    inner: Arc<Inner<T>>,
+}
+
+
+impl<T: Send + 'static> LeaseSlot<T> {
+    /// Create a new slot holding a value.
+    pub fn new(value: T) -> LeaseSlot<T> {
+        let inner = Arc::new(Inner {
+            state: Mutex::new(State {
+                value: Some(value),
+                lease: None,
+                next_id: 0,
+            }),
+        });
+
+        LeaseSlot { inner }
+    }
+
+
+    /// Lease the value out of the slot for at most `ttl`, after which `fallback` is installed in
+    /// its place. A `ttl` too long to be represented as a deadline never expires. Returns `None`
+    /// if the value is already leased out.
+    pub fn lease<'a>(&'a self, ttl: Duration, fallback: T) -> Option<LeaseGuard<'a, T>> {
+        let mut state = self.inner.lock();
+
+        let value = state.value.take()?;
+        let id = state.next_id;
+
+        state.next_id += 1;
+        state.lease = Some(Lease {
+            id,
+            timer: None,
+            fallback,
+        });
+
+        // The timer thread takes the slot's lock to expire the lease, so the timer must be started
+        // without holding it.
+        drop(state);
+
+        if let Some(deadline) = Instant::now().checked_add(ttl) {
+            let expiry = Arc::new(Expiry {
+                inner: Arc::downgrade(&self.inner),
+                id,
+            });
+            let timer = Timer::start(deadline, &Waker::from(expiry));
+
+            // If the lease has already expired, its timer has already left the queue.
+            if let Some(ref mut lease) = self.inner.lock().lease {
+                lease.timer = Some(timer);
+            }
+        }
+
+        Some(LeaseGuard {
+            slot: self,
+            id,
+            value: Some(value),
+        })
+    }
+}
+
+
+impl<T> LeaseSlot<T> {
+    /// Returns `true` if the slot's value is currently leased out.
+    pub fn is_leased(&self) -> bool {
+        self.inner.lock().lease.is_some()
+    }
+
+
+    /// Get a clone of the value in the slot, if it is not leased out.
+    pub fn get(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.inner.lock().value.clone()
+    }
+
+
+    /// End lease `id`, if it is still the current lease, filling the slot with the value supplied
+    /// by `refill`. Returns `false` if the lease has already expired.
+    fn check_in<F: FnOnce(T) -> T>(&self, id: u64, refill: F) -> bool {
+        let mut state = self.inner.lock();
+
+        if state.lease.as_ref().map(|lease| lease.id) != Some(id) {
+            return false;
+        }
+
+        let lease = state.lease.take().unwrap();
+        state.value = Some(refill(lease.fallback));
+
+        drop(state);
+        cancel(lease.timer);
+
+        true
+    }
+
+
+    fn is_current(&self, id: u64) -> bool {
+        self.inner.lock().lease.as_ref().map(|lease| lease.id) == Some(id)
+    }
+}
+
+
+impl<T> Drop for LeaseSlot<T> {
+    fn drop(&mut self) {
+        let lease = self.inner.lock().lease.take();
+
+        if let Some(lease) = lease {
+            cancel(lease.timer);
+        }
+    }
+}
+
+
+impl<T> fmt::Debug for LeaseSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LeaseSlot")
+            .field("leased", &self.is_leased())
+            .finish()
+    }
+}
+
+
+/// A value leased out of a `LeaseSlot`. `LeaseGuard<T>` dereferences to a `T`; when dropped, it
+/// returns the value to the slot, unless the lease has already expired and the fallback been
+/// installed, in which case the value is dropped.
+pub struct LeaseGuard<'a, T: 'a> {
+    slot: &'a LeaseSlot<T>,
+    id: u64,
+    value: Option<T>,
+}
+
+
+impl<'a, T> LeaseGuard<'a, T> {
+    /// Returns `true` if the lease's deadline has passed and the fallback has been installed.
+    pub fn is_expired(&self) -> bool {
+        !self.slot.is_current(self.id)
+    }
+
+
+    /// Return the value to the slot, handing it back instead if the lease has already expired.
+    pub fn release(mut self) -> Result<(), T> {
+        let mut value = self.value.take();
+
+        if self.slot.check_in(self.id, |_| value.take().unwrap()) {
+            Ok(())
+        } else {
+            Err(value.unwrap())
+        }
+    }
+
+
+    /// Keep the leased value, installing the fallback in the slot right away. If the lease has
+    /// already expired, the fallback is already in place.
+    pub fn into_inner(mut self) -> T {
+        self.slot.check_in(self.id, |fallback| fallback);
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for LeaseGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.slot.check_in(self.id, |_| value);
+        }
+    }
+}
+
+
+impl<'a, T> Deref for LeaseGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for LeaseGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    #[test]
+    fn return_before_deadline() {
+        let slot = LeaseSlot::new(1);
+
+        {
+            let mut lease = slot.lease(Duration::from_secs(60), 0).unwrap();
+
+            assert!(slot.lease(Duration::from_secs(60), 0).is_none());
+
+            *lease += 1;
+        }
+
+        assert!(!slot.is_leased());
+        assert_eq!(slot.get(), Some(2));
+    }
+
+    #[test]
+    fn expired_lease_installs_fallback() {
+        let slot = LeaseSlot::new(1);
+        let lease = slot.lease(Duration::from_millis(1), 0).unwrap();
+
+        while !lease.is_expired() {
+            thread::yield_now();
+        }
+
+        assert_eq!(slot.get(), Some(0));
+
+        drop(lease);
+
+        assert_eq!(slot.get(), Some(0));
+    }
+
+    #[test]
+    fn returned_lease_leaves_timer_queue() {
+        let slot = LeaseSlot::new(1);
+        let lease = slot.lease(Duration::from_secs(60), 0).unwrap();
+
+        let timer = slot.inner.lock().lease.as_ref().unwrap().timer.clone().unwrap();
+        assert!(timer.is_queued());
+
+        drop(lease);
+        assert!(!timer.is_queued());
+    }
+
+    #[test]
+    fn unbounded_lease_never_expires() {
+        let slot = LeaseSlot::new(1);
+        let lease = slot.lease(Duration::MAX, 0).unwrap();
+
+        assert!(!lease.is_expired());
+        assert_eq!(lease.release(), Ok(()));
+        assert_eq!(slot.get(), Some(1));
+    }
+
+    #[test]
+    fn keep_leased_value() {
+        let slot = LeaseSlot::new(1);
+
+        assert_eq!(slot.lease(Duration::from_secs(60), 0).unwrap().into_inner(), 1);
+        assert!(!slot.is_leased());
+        assert_eq!(slot.get(), Some(0));
+    }
+}
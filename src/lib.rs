@@ -106,25 +106,439 @@
 //! // Panics here!
 //! thing.steal_mut();
 //! ```
+//!
+//! ## Panics
+//!
+//! The panics raised by this crate carry typed payloads: a guard dropped without its value being
+//! restored panics with a `GuardLeaked`, stealing from an empty slot panics with a
+//! `StealFromNone` (or, from a `TestSlot` whose value is out, an `AlreadyStolen`), and
+//! transferring a guard to a full slot panics with a `TransferToOccupied`. All of them record the type of the value and the address of the slot involved, so
+//! supervisors using `std::panic::catch_unwind` can tell exactly which contract was violated.
+//!
+//! The flip side is that the default panic hook only prints string payloads, so without a hook
+//! of your own these panics are reported as just `Box<dyn Any>`. The crate installs no hook
+//! itself; to see the full message, install one which asks `violation_message` for it:
+//!
+//! ```
+//! # #[cfg(feature = "std")] {
+//! use empty_option::violation_message;
+//! use std::panic;
+//!
+//! let default_hook = panic::take_hook();
+//!
+//! panic::set_hook(Box::new(move |info| match violation_message(info.payload()) {
+//!     Some(message) => eprintln!("{}", message),
+//!     None => default_hook(info),
+//! }));
+//! # }
+//! ```
+//!
+//! ## Unwind safety
+//!
+//! Guards which borrow an `&mut Option<T>` (`OptionGuard`, `OptionGuardMut` and friends) are
+//! deliberately neither `UnwindSafe` nor `RefUnwindSafe`, just like the `&mut` they hold: a panic
+//! mid-update would otherwise let a half-modified value be observed after `catch_unwind`. Wrap
+//! code using them in `AssertUnwindSafe` once you have checked that is acceptable.
+//!
+//! The shared slot types (`SharedSlot`, `AsyncSlot`, `LeaseSlot`, `Pool`, `SlotRing` and
+//! `AtomicSmallSlot`) are both `UnwindSafe` and `RefUnwindSafe`, and so are their guards whenever
+//! `T` is. Their guards return the value to the slot while unwinding, so a panic can never leave
+//! a slot stuck as stolen; the value returned is whatever the guard held when the panic struck.
+//! `ConfigSlot` and `Finalizer` hold boxed closures, and like the closures themselves are neither.
+//!
+//! ## Threads and variance
+//!
+//! `OptionGuard` and `OptionGuardMut` are `Send` whenever `T` is, and `Sync` whenever `T` is, just
+//! like the `&mut Option<T>` they hold, so they can be handed to scoped threads along with their
+//! values.
+//!
+//! Both are invariant in `T`, again like `&mut Option<T>`, and deliberately so: a guard for a
+//! slot of `&'static str` must not be usable as a guard for a slot of some shorter-lived `&str`,
+//! or the shorter-lived value could be restored to a slot which outlives it:
+//!
+//! ```compile_fail
+//! # use empty_option::OptionGuard;
+//! fn shorten<'a, 'b>(guard: OptionGuard<'a, &'static str>) -> OptionGuard<'a, &'b str> {
+//!     guard
+//! }
+//! ```
+//!
+//! ## Strict mode
+//!
+//! The `strict` feature deprecates every entry point which panics when it finds a slot empty -
+//! `steal`, `steal_mut`, `steal_deref`, their counterparts on cells, pins, slices, tuples, `Slot`
+//! and `AtomicSmallSlot`, `GuardSet::steal`, `ConfigSlot::get`, `ConfigSlot::steal`, and the
+//! scoped-thread helpers `steal_scoped_threads` and `join_steal` - as well as `steal_strict`,
+//! which aborts instead. Together with `#![deny(deprecated)]`, this turns any use of them into a
+//! build error, so safety-critical code can be sure it only uses the crate's fallible,
+//! non-panicking APIs. (The feature deprecates rather than removes them, so that enabling it
+//! cannot break other crates in the build.)
+//!
+//! ## `no_std`
+//!
+//! The guards themselves need nothing beyond `core`, so with default features disabled the
+//! crate is `#![no_std]`, for use on interrupt-shared state in embedded code. The `alloc`
+//! feature adds back the pieces which need a heap (`AnyGuard`, `ConfigSlot`, `Finalizer`, the
+//! `Vec` and `String` placeholders, `BTreeMap` steals and `pop_guard`), and the default `std`
+//! feature adds the rest: the shared slot types, `HashMap` steals, `io` passthrough and
+//! `LogAndLeaveNone`. `SpinSlot` and `AtomicSmallSlot` are available either way.
+//!
+//! Without `std` there are no typed panic payloads, so `GuardLeaked`, `StealFromNone` and
+//! `TransferToOccupied` are raised as panic messages instead.
+
+// The crate's own uses of its panicking entry points are all on slots known to be full.
+#![cfg_attr(feature = "strict", allow(deprecated))]
+// Tests always link `std`, for `catch_unwind` and friends, whichever features are enabled.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "derive")]
+extern crate empty_option_derive;
+// Everything outside the `std`-gated modules is written against `std::` paths which `core` also
+// provides, so without `std` those paths resolve to `core`.
+#[cfg(not(any(feature = "std", test)))]
+extern crate core as std;
 
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+use std::borrow::{Borrow, BorrowMut};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Read, Write};
+use std::marker::PhantomData;
 use std::mem;
 use std::ops::{Deref, DerefMut};
+use std::option;
+use std::panic::Location;
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll};
+
+
+#[cfg(feature = "alloc")]
+mod any_guard;
+#[cfg(feature = "std")]
+mod async_slot;
+mod cell;
+#[cfg(target_has_atomic = "64")]
+mod atomic;
+#[cfg(feature = "std")]
+mod channel;
+#[cfg(feature = "alloc")]
+mod config;
+mod entry;
+mod fallback;
+#[cfg(feature = "alloc")]
+mod finalizer;
+mod flatten;
+mod future;
+mod guard_set;
+mod leak;
+#[cfg(feature = "std")]
+mod lease;
+#[cfg(feature = "alloc")]
+mod map;
+#[cfg(feature = "std")]
+mod mutex;
+mod pinned;
+mod placeholder;
+#[cfg(feature = "std")]
+mod poison;
+#[cfg(feature = "std")]
+mod pool;
+mod replace;
+mod result;
+#[cfg(feature = "std")]
+mod ring;
+#[cfg(feature = "std")]
+mod scoped;
+mod sentinel;
+#[cfg(feature = "std")]
+mod shared;
+mod slice;
+mod slot;
+#[cfg(feature = "spin")]
+mod spin;
+mod swap;
+mod take;
+#[cfg(feature = "std")]
+mod test_slot;
+#[cfg(feature = "std")]
+mod timer;
+mod tuple;
+#[cfg(feature = "alloc")]
+mod vec;
+mod violation;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "stats")]
+pub mod stats;
+
+#[cfg(test)]
+mod test_util;
+
+#[cfg(feature = "alloc")]
+pub use any_guard::{AnyGuard, RestoreOnDrop};
+#[cfg(feature = "std")]
+pub use async_slot::{
+    AsyncSlot, AsyncSlotGuard, AsyncSteal, AsyncStealOwned, AsyncStealTimeout, Occupancy,
+    OccupancyChanged, OccupancyWatch, OnRestoreFailure, OwnedAsyncSlotGuard, RestoreFrom,
+    StealTimedOut,
+};
+#[cfg(target_has_atomic = "64")]
+pub use atomic::{AtomicSmallGuard, AtomicSmallSlot, NoPadding};
+pub use cell::{CellOptionExt, CopyGuard, UnsafeCellOptionExt};
+#[cfg(feature = "std")]
+pub use channel::{ReceiverStealExt, SendBackGuard};
+#[cfg(feature = "alloc")]
+pub use config::{ConfigGuard, ConfigSlot};
+#[cfg(feature = "derive")]
+pub use empty_option_derive::Placeholder;
+pub use entry::{GuardEntry, OccupiedGuard, VacantGuard};
+pub use fallback::FallbackGuard;
+#[cfg(feature = "alloc")]
+pub use finalizer::Finalizer;
+pub use flatten::{FlattenGuard, FlattenOptionExt};
+pub use future::Guarded;
+pub use guard_set::GuardSet;
+pub use leak::{Abort, OnLeak, Panic, RestoreDefault};
+#[cfg(feature = "std")]
+pub use leak::LogAndLeaveNone;
+#[cfg(feature = "std")]
+pub use lease::{LeaseGuard, LeaseSlot};
+#[cfg(feature = "alloc")]
+pub use map::{BTreeMapEntryGuard, BTreeMapStealExt, RangeGuard};
+#[cfg(feature = "std")]
+pub use map::{HashMapEntryGuard, HashMapStealExt};
+#[cfg(feature = "std")]
+pub use mutex::{MutexOptionExt, MutexOptionGuard, OnPoison, Poisoned};
+pub use pinned::{PinGuardMut, PinOptionExt};
+pub use placeholder::{Placeholder, PlaceholderExt, PlaceholderGuard};
+#[cfg(feature = "std")]
+pub use pool::{Pool, PoolGuard};
+pub use replace::{replace_guarded, ReplaceGuard};
+pub use result::{ResultGuard, ResultStealExt};
+#[cfg(feature = "std")]
+pub use ring::SlotRing;
+#[cfg(feature = "std")]
+pub use scoped::{join_steal, steal_scoped_threads, try_join_steal, try_steal_scoped_threads};
+pub use sentinel::SentinelGuard;
+#[cfg(feature = "std")]
+pub use shared::{SharedSlot, SharedSlotGuard};
+pub use slice::SliceOptionExt;
+pub use slot::Slot;
+#[cfg(feature = "spin")]
+pub use spin::{SpinSlot, SpinSlotGuard};
+pub use swap::SwapGuard;
+pub use take::{
+    take_guarded, take_in_place, take_in_place_or_recover, take_strict, StrictTakeGuard, TakeGuard,
+};
+#[cfg(feature = "std")]
+pub use test_slot::{SlotEvent, TestSlot, TestSlotGuard};
+pub use tuple::{RestoreTuple, StealTuple, TupleGuard, TupleGuardMut};
+#[cfg(feature = "alloc")]
+pub use vec::{PopGuard, VecStealExt};
+pub use violation::{AlreadyStolen, GuardLeaked, StealFromNone, TransferToOccupied};
+#[cfg(feature = "std")]
+pub use violation::violation_message;
+
+
+/// A value taken by `EmptyOptionExt::try_steal_strict`, along with its aborting guard.
+type StrictSteal<'a, T> = Result<(OptionGuard<'a, T, Abort>, T), StealError>;
 
 
 /// Extension trait providing nice method sugar for `steal` and `steal_mut`.
+///
+/// Method calls see through smart pointers, so these work directly on a `Box<Option<T>>`,
+/// `RefMut<Option<T>>`, `MutexGuard<Option<T>>` or anything else which is `DerefMut` to an
+/// `Option<T>`, with no `&mut **` reborrow. The resulting guard borrows the smart pointer, so it
+/// cannot outlive a lock or a `RefCell` borrow.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// use std::cell::RefCell;
+///
+/// let state = RefCell::new(Some(1));
+/// let mut boxed = Box::new(Some(10));
+///
+/// *state.borrow_mut().steal_mut() += 1;
+/// *boxed.steal_mut() += 1;
+///
+/// assert_eq!((state.into_inner(), *boxed), (Some(2), Some(11)));
+/// ```
 pub trait EmptyOptionExt {
     type Inner;
 
     /// Take a value out of an option, providing a guard which panics if the value is not returned.
     /// Panics on `None`.
-    fn steal(&mut self) -> (OptionGuard<Self::Inner>, Self::Inner);
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids; use `try_steal`")
+    )]
+    fn steal<'a>(&'a mut self) -> (OptionGuard<'a, Self::Inner>, Self::Inner);
+
+    /// Like `steal`, but with a guard which deals with being leaked according to the `OnLeak`
+    /// policy `P` rather than by panicking. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_with_policy<'a, P>(&'a mut self) -> (OptionGuard<'a, Self::Inner, P>, Self::Inner)
+    where
+        P: OnLeak<Self::Inner>;
+
+    /// Like `steal`, but recording `context` - what the value is being stolen for - in the panic
+    /// raised if the guard is leaked, or if the option is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_with_context<'a>(
+        &'a mut self,
+        context: &'static str,
+    ) -> (OptionGuard<'a, Self::Inner>, Self::Inner);
+
+    /// Like `steal`, but with a guard which writes `fallback` back into the option if it is
+    /// dropped without a value being restored, instead of panicking. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_with_fallback<'a>(
+        &'a mut self,
+        fallback: Self::Inner,
+    ) -> (FallbackGuard<'a, Self::Inner>, Self::Inner);
+
+    /// Like `steal`, but leaving `placeholder` in the option while the value is stolen, instead of
+    /// `None`. Restoring the value hands the placeholder back. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_replace<'a>(
+        &'a mut self,
+        placeholder: Self::Inner,
+    ) -> (SentinelGuard<'a, Self::Inner>, Self::Inner);
+
+    /// Take the values out of this option and `other` together, providing a guard which panics
+    /// unless both are refilled, and which can swap the values between them. Panics, without
+    /// taking anything, if either option is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn swap_steal<'a>(
+        &'a mut self,
+        other: &'a mut Option<Self::Inner>,
+    ) -> (SwapGuard<'a, Self::Inner>, Self::Inner, Self::Inner);
+
+    /// Like `steal`, but never unwinding: the guard aborts the process with a diagnostic if it is
+    /// leaked, and so does stealing from `None`. For FFI callbacks and other code which must not
+    /// unwind, where a panic in drop glue would otherwise abort with a far less helpful message.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "aborts on `None`, which the `strict` feature forbids; use `try_steal_strict`")
+    )]
+    fn steal_strict<'a>(&'a mut self) -> (OptionGuard<'a, Self::Inner, Abort>, Self::Inner);
+
+    /// Like `steal_strict`, but returning a `StealError` on `None` instead of aborting. The guard
+    /// still aborts if it is leaked.
+    fn try_steal_strict<'a>(&'a mut self) -> StrictSteal<'a, Self::Inner>;
+
+    /// Like `steal`, but returning a `StealError` on `None` instead of panicking.
+    fn try_steal<'a>(
+        &'a mut self,
+    ) -> Result<(OptionGuard<'a, Self::Inner>, Self::Inner), StealError>;
 
     /// Take a value out of an option, providing a guard which returns the value unless consumed by
     /// `OptionGuardMut::into_inner`. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids; use `try_steal_mut`")
+    )]
     fn steal_mut<'a>(&'a mut self) -> OptionGuardMut<'a, Self::Inner>;
+
+    /// Like `steal_mut`, but returning a `StealError` on `None` instead of panicking.
+    fn try_steal_mut<'a>(&'a mut self) -> Result<OptionGuardMut<'a, Self::Inner>, StealError>;
+
+    /// Like `steal_mut`, but filling a `None` with `Default::default()` first instead of
+    /// panicking.
+    fn steal_mut_or_default<'a>(&'a mut self) -> OptionGuardMut<'a, Self::Inner>
+    where
+        Self::Inner: Default;
+
+    /// Like `steal`, but creating the value with `f` on `None` instead of panicking, after the
+    /// manner of `Option::get_or_insert_with`.
+    fn steal_or_insert_with<'a, F>(
+        &'a mut self,
+        f: F,
+    ) -> (OptionGuard<'a, Self::Inner>, Self::Inner)
+    where
+        F: FnOnce() -> Self::Inner;
+
+    /// Like `steal_mut`, but creating the value with `f` on `None` instead of panicking.
+    fn steal_mut_or_insert_with<'a, F>(&'a mut self, f: F) -> OptionGuardMut<'a, Self::Inner>
+    where
+        F: FnOnce() -> Self::Inner;
+
+    /// Like `steal_mut`, but providing a guard which dereferences straight through to the target
+    /// of the stolen value - a `str` for an `Option<String>`, say. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_deref<'a>(&'a mut self) -> OptionGuardDeref<'a, Self::Inner>
+    where
+        Self::Inner: DerefMut;
+
+    /// Look at the option as an entry, either `GuardEntry::Occupied` with a value to steal, or
+    /// `GuardEntry::Vacant` ready to be filled. Never panics.
+    fn guard_entry<'a>(&'a mut self) -> GuardEntry<'a, Self::Inner>;
+}
+
+
+/// The error returned by `EmptyOptionExt::try_steal` and `EmptyOptionExt::try_steal_mut` when the
+/// option is `None`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{EmptyOptionExt, StealError};
+/// fn next_id(ids: &mut Option<u32>) -> Result<u32, StealError> {
+///     let (guard, id) = ids.try_steal()?;
+///
+///     guard.restore(id + 1);
+///     Ok(id)
+/// }
+///
+/// let mut ids = Some(7);
+///
+/// assert_eq!(next_id(&mut ids), Ok(7));
+/// assert_eq!(ids, Some(8));
+/// assert_eq!(next_id(&mut None), Err(StealError));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StealError;
+
+
+impl fmt::Display for StealError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("attempted to steal from None")
+    }
 }
 
 
+impl Error for StealError {}
+
+
 /// An option which has had its value taken. On `Drop`, `OptionGuard` will panic - in order to
 /// prevent a panic, the stolen value must be moved back in with `OptionGuard::restore`.
 ///
@@ -179,31 +593,326 @@ pub trait EmptyOptionExt {
 /// 
 /// guard.restore(5);
 /// ```
-pub struct OptionGuard<'a, T: 'a> {
+///
+/// What happens when a guard is leaked is decided by its `OnLeak` policy, which is `Panic` unless
+/// another was chosen with `EmptyOptionExt::steal_with_policy`. With the `backtrace` feature, the
+/// guard also captures a backtrace when it is created (subject to `RUST_BACKTRACE` and
+/// `RUST_LIB_BACKTRACE`, as for `Backtrace::capture`), and prints it to standard error if it is
+/// leaked.
+pub struct OptionGuard<'a, T: 'a, P: OnLeak<T> = Panic> {
     opt: &'a mut Option<T>,
+    location: &'static Location<'static>,
+    context: Option<&'static str>,
+    #[cfg(feature = "stats")]
+    _hold: stats::Hold,
+    #[cfg(feature = "backtrace")]
+    backtrace: Backtrace,
+    _policy: PhantomData<P>,
+}
+
+
+impl<'a, T, P: OnLeak<T>> fmt::Debug for OptionGuard<'a, T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The guard holds no value to show, so `T` needn't be `Debug`.
+        f.debug_struct("OptionGuard")
+            .field("location", &self.location)
+            .field("context", &self.context)
+            .finish()
+    }
 }
 
 
-impl<'a, T> Drop for OptionGuard<'a, T> {
+impl<'a, T, P: OnLeak<T>> Drop for OptionGuard<'a, T, P> {
     fn drop(&mut self) {
-        panic!("`Some` value was never restored to a victimized Option!");
+        #[cfg(feature = "backtrace")]
+        violation::report_backtrace(&self.backtrace);
+
+        let leak = violation::leak_of::<T, _>(self.opt, self.location, self.context);
+
+        P::leaked(self.opt, leak);
     }
 }
 
 
-impl<'a, T> OptionGuard<'a, T> {
-    fn new(opt: &'a mut Option<T>) -> OptionGuard<'a, T> {
+impl<'a, T, P: OnLeak<T>> OptionGuard<'a, T, P> {
+    #[track_caller]
+    fn new(opt: &'a mut Option<T>) -> OptionGuard<'a, T, P> {
         OptionGuard {
-            opt
+            opt,
+            location: Location::caller(),
+            context: None,
+            #[cfg(feature = "stats")]
+            _hold: stats::Hold::start(),
+            #[cfg(feature = "backtrace")]
+            backtrace: Backtrace::capture(),
+            _policy: PhantomData,
         }
     }
 
 
+    /// Time the steal with `hold`, which was started by another guard, in place of this guard's
+    /// own.
+    #[cfg(feature = "stats")]
+    fn with_hold(mut self, hold: stats::Hold) -> OptionGuard<'a, T, P> {
+        mem::forget(mem::replace(&mut self._hold, hold));
+        self
+    }
+
+
     /// Restore a stolen value to an `Option`.
     pub fn restore(self, obj: T) {
-        *self.opt = Some(obj);
+        *self.into_slot() = Some(obj);
+    }
+
+
+    /// Restore anything convertible into a `T`, such as a single state of a state machine which
+    /// implements `Into` its enum. If the conversion panics, the `Option` is left `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut name: Option<String> = Some(String::from("anonymous"));
+    ///
+    /// let (guard, _) = name.steal();
+    /// guard.restore_into("alice");
+    ///
+    /// assert_eq!(name.as_deref(), Some("alice"));
+    /// ```
+    pub fn restore_into<U: Into<T>>(self, obj: U) {
+        self.restore_with(|| obj.into());
+    }
+
+
+    /// Restore a stolen value to an `Option`, returning a reference to it in its new place, as
+    /// `Option::insert` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut log = Some(vec!["started"]);
+    ///
+    /// let (guard, entries) = log.steal();
+    /// let entries = guard.restore_insert(entries);
+    ///
+    /// entries.push("restored");
+    ///
+    /// assert_eq!(log, Some(vec!["started", "restored"]));
+    /// ```
+    pub fn restore_insert(self, obj: T) -> &'a mut T {
+        self.into_slot().insert(obj)
+    }
+
+
+    /// Settle the guard with a value, but keep hold of it through an `OptionGuardMut`, which
+    /// returns it to the `Option` when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut total = Some(1);
+    ///
+    /// {
+    ///     let (guard, one) = total.steal();
+    ///     let mut stolen = guard.restore_mut(one * 10);
+    ///
+    ///     *stolen += 5;
+    /// }
+    ///
+    /// assert_eq!(total, Some(15));
+    /// ```
+    pub fn restore_mut(self, obj: T) -> OptionGuardMut<'a, T> {
+        let this = mem::ManuallyDrop::new(self);
+        #[cfg(feature = "backtrace")]
+        drop(unsafe { ptr::read(&this.backtrace) });
+
+        // The value has been out since the original steal, so its hold carries on.
+        OptionGuardMut {
+            origin: unsafe { ptr::read(&this.opt) },
+            value: Some(obj),
+            mutated: false,
+            #[cfg(feature = "stats")]
+            _hold: unsafe { ptr::read(&this._hold) },
+        }
+    }
+
+
+    /// Restore a value computed by `f` to an `Option`, only building it once it is needed. If `f`
+    /// panics, the `Option` is left `None`.
+    pub fn restore_with<F: FnOnce() -> T>(self, f: F) {
+        // Defuse the guard before calling `f`, so that a panic in `f` cannot panic it again.
+        let opt = self.into_slot();
+
+        *opt = Some(f());
+    }
+
+
+    /// Restore a value by moving it out of another `Option`, leaving that one `None`. If `source`
+    /// is `None`, nothing is moved and the guard is handed back, still unrestored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut current = Some("draft");
+    /// let mut pending = Some("final");
+    ///
+    /// let (guard, _draft) = current.steal();
+    /// assert!(guard.restore_from(&mut pending).is_ok());
+    ///
+    /// assert_eq!((current, pending), (Some("final"), None));
+    /// ```
+    pub fn restore_from(self, source: &mut Option<T>) -> Result<(), Self> {
+        match source.take() {
+            Some(obj) => {
+                self.restore(obj);
+                Ok(())
+            }
+            None => Err(self),
+        }
+    }
+
+
+    /// Move the guard's obligation to another `Option`, which must then be restored to instead.
+    /// This `Option` is left `None`. Panics with a `TransferToOccupied` if `other` already holds a
+    /// value, in which case this `Option` is still left `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut idle = Some(String::from("connection"));
+    /// let mut busy = None;
+    ///
+    /// {
+    ///     let (guard, connection) = idle.steal();
+    ///     let guard = guard.transfer(&mut busy);
+    ///
+    ///     guard.restore(connection);
+    /// }
+    ///
+    /// assert_eq!((idle, busy), (None, Some(String::from("connection"))));
+    /// ```
+    #[track_caller]
+    pub fn transfer<'b>(self, other: &'b mut Option<T>) -> OptionGuard<'b, T, P> {
+        let context = self.context;
+
+        if other.is_some() {
+            // Defuse the guard first, so that unwinding cannot drop it and panic a second time.
+            self.into_slot();
+            violation::transfer_to_occupied::<T, _>(other, context);
+        }
+
+        // Only the hold and backtrace are kept, and only when the features which add them are on.
+        #[allow(unused_variables)]
+        let this = mem::ManuallyDrop::new(self);
+
+        // The value has been out since the original steal, so its hold and backtrace carry on.
+        OptionGuard {
+            opt: other,
+            location: Location::caller(),
+            context,
+            #[cfg(feature = "stats")]
+            _hold: unsafe { ptr::read(&this._hold) },
+            #[cfg(feature = "backtrace")]
+            backtrace: unsafe { ptr::read(&this.backtrace) },
+            _policy: PhantomData,
+        }
+    }
+
+
+    /// Consume the guard without restoring anything or panicking, keeping the stolen value and
+    /// leaving the `Option` `None`.
+    pub fn defuse(self) {
+        self.into_slot();
+    }
+
+
+    /// Register a transformation to apply automatically to the value when it is restored, such
+    /// as normalizing or re-sealing it, so that no restore site can forget to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut name = Some(String::from("Alice"));
+    ///
+    /// {
+    ///     let (guard, stolen) = name.steal();
+    ///     let guard = guard.map_restore(|name: String| name.to_lowercase());
+    ///
+    ///     guard.restore(stolen + " Smith");
+    /// }
+    ///
+    /// assert_eq!(name, Some(String::from("alice smith")));
+    /// ```
+    pub fn map_restore<F>(self, map: F) -> MappedOptionGuard<'a, T, F, P>
+    where
+        F: FnOnce(T) -> T,
+    {
+        MappedOptionGuard { guard: self, map }
+    }
 
+
+    /// Take the guard apart, handing back the victimized `Option` without restoring anything to
+    /// it or panicking.
+    ///
+    /// # Safety
+    ///
+    /// The caller takes over the guard's obligation: the value taken from the `Option` must be
+    /// restored to it (or the `Option` otherwise dealt with) just as the guard would require.
+    pub unsafe fn into_parts(self) -> &'a mut Option<T> {
+        self.into_slot()
+    }
+
+
+    /// Defuse the guard, handing back the victimized `Option` without restoring anything to it.
+    fn into_slot(self) -> &'a mut Option<T> {
+        let opt = unsafe { ptr::read(&self.opt) };
+        #[cfg(feature = "stats")]
+        drop(unsafe { ptr::read(&self._hold) });
+        #[cfg(feature = "backtrace")]
+        drop(unsafe { ptr::read(&self.backtrace) });
         mem::forget(self);
+        opt
+    }
+}
+
+
+impl<'a, T> OptionGuard<'a, T> {
+    /// Assemble a guard for an `Option` whose value the caller has already taken.
+    ///
+    /// # Safety
+    ///
+    /// `opt` must be empty, and the value taken from it must be in the caller's custody, ready to
+    /// be handed to `OptionGuard::restore`. Abstractions built on top of `OptionGuard` may rely on
+    /// the slot of a live guard being vacant, and on the value being restored to it exactly once.
+    #[track_caller]
+    pub unsafe fn from_parts(opt: &'a mut Option<T>) -> OptionGuard<'a, T> {
+        OptionGuard::new(opt)
+    }
+}
+
+
+/// An `OptionGuard` with a transformation to apply at restore time, produced by
+/// `OptionGuard::map_restore`. Like an `OptionGuard`, it panics (or applies its `OnLeak` policy)
+/// when dropped unless a value is restored with `MappedOptionGuard::restore`.
+pub struct MappedOptionGuard<'a, T: 'a, F, P: OnLeak<T> = Panic> {
+    guard: OptionGuard<'a, T, P>,
+    map: F,
+}
+
+
+impl<'a, T, F: FnOnce(T) -> T, P: OnLeak<T>> MappedOptionGuard<'a, T, F, P> {
+    /// Apply the transformation to a value and restore the result to the `Option`. If the
+    /// transformation panics, the `Option` is left `None`.
+    pub fn restore(self, obj: T) {
+        let MappedOptionGuard { guard, map } = self;
+        let opt = guard.into_slot();
+
+        *opt = Some(map(obj));
     }
 }
 
@@ -247,7 +956,10 @@ impl<'a, T> OptionGuard<'a, T> {
 /// assert_eq!(thing, None);
 /// ```
 ///
-/// Calling `steal_mut` on a `None` immediately panics:
+/// Calling `steal_mut` on a `None` immediately panics, so a live `OptionGuardMut` always holds a
+/// value and dereferencing it never panics. To handle an empty option without a panic, use
+/// `EmptyOptionExt::try_steal_mut`, `steal_mut_or_default`, `steal_mut_or_insert_with` or
+/// `guard_entry` instead.
 ///
 /// ```rust,should_panic
 /// use empty_option::EmptyOptionExt;
@@ -260,132 +972,1301 @@ impl<'a, T> OptionGuard<'a, T> {
 pub struct OptionGuardMut<'a, T: 'a> {
     origin: &'a mut Option<T>,
     value: Option<T>,
+    mutated: bool,
+    #[cfg(feature = "stats")]
+    _hold: stats::Hold,
 }
 
 
 impl<'a, T> Drop for OptionGuardMut<'a, T> {
     fn drop(&mut self) {
+        // This cannot be skipped for values which were only read: the steal left `origin` empty,
+        // so the value has to be moved back whether or not it changed.
         *self.origin = self.value.take();
     }
 }
 
 
 impl<'a, T> OptionGuardMut<'a, T> {
+    /// Assemble a guard from an `Option` and the value the caller has already taken from it.
+    ///
+    /// # Safety
+    ///
+    /// `origin` must be empty, and `value` must be the value taken from it (or a replacement for
+    /// it). Abstractions built on top of `OptionGuardMut` may rely on the slot of a live guard
+    /// being vacant until the guard returns its value.
+    #[track_caller]
+    pub unsafe fn from_parts(origin: &'a mut Option<T>, value: T) -> OptionGuardMut<'a, T> {
+        OptionGuardMut {
+            origin,
+            value: Some(value),
+            mutated: false,
+            #[cfg(feature = "stats")]
+            _hold: stats::Hold::start(),
+        }
+    }
+
+
+    /// Take the guard apart into the `Option` and the value stolen from it, without returning the
+    /// value.
+    ///
+    /// # Safety
+    ///
+    /// The caller takes over the guard's obligation: the value must be returned to the `Option`
+    /// (or deliberately kept) just as the guard would have done.
+    pub unsafe fn into_parts(mut self) -> (&'a mut Option<T>, T) {
+        let value = self.value.take().unwrap();
+        let origin = ptr::read(&self.origin);
+        #[cfg(feature = "stats")]
+        drop(ptr::read(&self._hold));
+        mem::forget(self);
+
+        (origin, value)
+    }
+
+
     /// Keep the value stolen from the `Option` and do not return it.
     pub fn into_inner(mut self) -> T {
         self.value.take().unwrap()
     }
-}
 
 
-impl<'a, T> Deref for OptionGuardMut<'a, T> {
-    type Target = T;
+    /// Escalate to a by-value steal: take ownership of the value, and exchange this guard for an
+    /// `OptionGuard` which insists that a value be restored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut buffer = Some(String::from("draft"));
+    ///
+    /// {
+    ///     let stolen = buffer.steal_mut();
+    ///
+    ///     if stolen.starts_with("draft") {
+    ///         let (guard, draft) = stolen.into_guard();
+    ///
+    ///         guard.restore(draft.replace("draft", "final"));
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(buffer, Some(String::from("final")));
+    /// ```
+    #[track_caller]
+    pub fn into_guard(mut self) -> (OptionGuard<'a, T>, T) {
+        let value = self.value.take().unwrap();
+        let guard = OptionGuard::new(unsafe { ptr::read(&self.origin) });
 
-    fn deref(&self) -> &T {
-        self.value.as_ref().unwrap()
+        // The value has been out since the original steal, so its hold carries on.
+        #[cfg(feature = "stats")]
+        let guard = guard.with_hold(unsafe { ptr::read(&self._hold) });
+
+        mem::forget(self);
+
+        (guard, value)
     }
-}
 
 
-impl<'a, T> DerefMut for OptionGuardMut<'a, T> {
-    fn deref_mut(&mut self) -> &mut T {
-        self.value.as_mut().unwrap()
+    /// Return the value to the `Option` right away, keeping a reference to it there for the rest of
+    /// the original borrow.
+    pub fn into_mut(self) -> &'a mut T {
+        let (origin, value) = unsafe { self.into_parts() };
+
+        origin.insert(value)
     }
-}
 
 
-impl<T> EmptyOptionExt for Option<T> {
-    type Inner = T;
+    /// Drop the stolen value, leaving the `Option` `None`.
+    pub fn discard(self) {
+        drop(self.into_inner());
+    }
 
-    fn steal(&mut self) -> (OptionGuard<T>, T) {
-        let value = self.take().expect("attempted to steal from None");
-        (OptionGuard::new(self), value)
+
+    /// Swap a new value in for the stolen one, returning the old value. Since this shadows any
+    /// `replace` method of `T` itself (such as `str::replace`), call those as `(*guard).replace`.
+    pub fn replace(&mut self, value: T) -> T {
+        mem::replace(&mut **self, value)
     }
 
-    fn steal_mut(&mut self) -> OptionGuardMut<T> {
-        let value = Some(self.take().expect("attempted to steal from None"));
 
-        OptionGuardMut {
-            origin: self,
-            value,
-        }
+    /// A clone of the stolen value, leaving the guard in place - a snapshot to log, or to compare
+    /// against after a transition. Like `replace`, this shadows any `cloned` method of `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut path = Some(vec!["home"]);
+    ///
+    /// let mut stolen = path.steal_mut();
+    /// let before = stolen.cloned();
+    ///
+    /// stolen.push("user");
+    ///
+    /// assert_eq!((before, stolen.cloned()), (vec!["home"], vec!["home", "user"]));
+    /// ```
+    pub fn cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        (**self).clone()
     }
-}
 
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// A copy of the stolen value, leaving the guard in place. Like `replace`, this shadows any
+    /// `copied` method of `T`.
+    pub fn copied(&self) -> T
+    where
+        T: Copy,
+    {
+        **self
+    }
 
-    #[test]
-    fn catch_and_release() {
-        let mut thing = Some(5);
 
-        {
-            let (guard, five) = thing.steal();
+    /// Transform the stolen value by-value, for transitions which consume the old value, such as
+    /// builders and enum state machines. If `f` panics, the value is lost and the `Option` is left
+    /// `None`. Like `replace`, this shadows any `map` method of `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// #[derive(Debug, PartialEq)]
+    /// enum Door {
+    ///     Open(String),
+    ///     Closed(String),
+    /// }
+    ///
+    /// let mut door = Some(Door::Open(String::from("front")));
+    ///
+    /// door.steal_mut().map(|door| match door {
+    ///     Door::Open(name) => Door::Closed(name),
+    ///     closed => closed,
+    /// });
+    ///
+    /// assert_eq!(door, Some(Door::Closed(String::from("front"))));
+    /// ```
+    pub fn map<F: FnOnce(T) -> T>(mut self, f: F) -> OptionGuardMut<'a, T> {
+        let value = f(self.value.take().unwrap());
 
-            assert_eq!(five, 5);
+        self.value = Some(value);
+        self.mutated = true;
+        self
+    }
 
-            guard.restore(6);
-        }
 
-        assert_eq!(thing, Some(6));
+    /// Transform the stolen value by-value with a fallible function. On failure, `f` hands back
+    /// the value (untouched or otherwise) along with its error, and both the guard and the error
+    /// are returned, so the value still goes back to the `Option` as usual. If `f` panics, the
+    /// value is lost and the `Option` is left `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut version = Some(String::from("v1"));
+    ///
+    /// {
+    ///     let upgrade = |v: String| {
+    ///         if v == "v1" { Ok(String::from("v2")) } else { Err((v, "unknown version")) }
+    ///     };
+    ///
+    ///     let stolen = version.steal_mut().try_map(upgrade).ok().unwrap();
+    ///     assert_eq!(*stolen, "v2");
+    ///
+    ///     let (stolen, error) = stolen.try_map(upgrade).err().unwrap();
+    ///     assert_eq!((&**stolen, error), ("v2", "unknown version"));
+    /// }
+    ///
+    /// assert_eq!(version, Some(String::from("v2")));
+    /// ```
+    pub fn try_map<E, F>(
+        mut self,
+        f: F,
+    ) -> Result<OptionGuardMut<'a, T>, (OptionGuardMut<'a, T>, E)>
+    where
+        F: FnOnce(T) -> Result<T, (T, E)>,
+    {
+        let result = f(self.value.take().unwrap());
+        self.mutated = true;
+
+        match result {
+            Ok(value) => {
+                self.value = Some(value);
+                Ok(self)
+            }
+            Err((value, error)) => {
+                self.value = Some(value);
+                Err((self, error))
+            }
+        }
     }
 
-    #[test]
-    #[should_panic]
-    fn catch_and_keep() {
-        let mut thing = Some(5);
 
-        let (_, _) = thing.steal();
+    /// Returns `true` if the stolen value may have been changed through the guard: that is, if it
+    /// was ever mutably dereferenced, replaced or mapped. Reading the value never sets this.
+    ///
+    /// The value is moved back into the `Option` on drop either way. The steal moved it out,
+    /// leaving the `Option` empty, so an unchanged value cannot skip the write-back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::EmptyOptionExt;
+    /// let mut state = Some(vec![1, 2]);
+    ///
+    /// let mut stolen = state.steal_mut();
+    /// assert_eq!(stolen.len(), 2);
+    /// assert!(!stolen.was_mutated());
+    ///
+    /// stolen.push(3);
+    /// assert!(stolen.was_mutated());
+    /// ```
+    pub fn was_mutated(&self) -> bool {
+        self.mutated
+    }
 
-        // Never return the value!
+
+    /// Give up mutable access to the stolen value, producing an `OptionGuardRef` which only
+    /// dereferences to a `&T` but still returns the value to the `Option` when dropped.
+    pub fn freeze(self) -> OptionGuardRef<'a, T> {
+        OptionGuardRef { inner: self }
     }
 
-    #[test]
-    #[should_panic]
-    fn catch_from_none() {
-        let mut thing = None;
 
-        let (guard, _) = thing.steal();
+    /// Wrap the guard in an `OpaqueGuardMut`, which is `Debug` whatever `T` is.
+    pub fn opaque(self) -> OpaqueGuardMut<'a, T> {
+        OpaqueGuardMut { inner: self }
+    }
+}
 
-        guard.restore(5);
+
+impl<'a, T: fmt::Debug> fmt::Debug for OptionGuardMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OptionGuardMut")
+            .field("value", &**self)
+            .field("mutated", &self.mutated)
+            .finish()
     }
+}
 
-    #[test]
-    fn mut_and_release() {
-        let mut thing = Some(5);
 
-        {
-            let mut stolen = thing.steal_mut();
+impl<'a, T: fmt::Display> fmt::Display for OptionGuardMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
 
-            assert_eq!(*stolen, 5);
 
-            *stolen = 6;
-        }
+/// Iterating over an `OptionGuardMut` consumes it with `OptionGuardMut::into_inner`, yielding the
+/// stolen value once and leaving the `Option` `None`, as iterating over an `Option` does.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// let mut pending = Some(3);
+/// let mut done = vec![1, 2];
+///
+/// done.extend(pending.steal_mut());
+///
+/// assert_eq!((pending, done), (None, vec![1, 2, 3]));
+/// ```
+///
+/// Because of this, the guard cannot itself be an `Iterator` when the stolen value is one, since
+/// every `Iterator` is also `IntoIterator`. The iterator's methods can still be called straight
+/// through the guard, though, and `&mut *guard` hands it on to code taking an `impl Iterator`:
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// let mut words = Some("one two three".split(' '));
+///
+/// let mut stolen = words.steal_mut();
+/// assert_eq!(stolen.next(), Some("one"));
+/// assert_eq!((&mut *stolen).count(), 2);
+/// ```
+impl<'a, T> IntoIterator for OptionGuardMut<'a, T> {
+    type Item = T;
+    type IntoIter = option::IntoIter<T>;
 
-        assert_eq!(thing, Some(6));
+    fn into_iter(self) -> option::IntoIter<T> {
+        Some(self.into_inner()).into_iter()
     }
+}
 
-    #[test]
-    fn mut_and_keep() {
-        let mut thing = Some(5);
-        
-        {
-            // Keep the thing!
-            let stolen = thing.steal_mut().into_inner();
 
-            assert_eq!(stolen, 5);
-        }
+/// A stolen reader can be read through its guard, so the guard can be passed on to code taking
+/// an `impl Read`. Likewise for `BufRead` and `Write`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// use std::io::{Cursor, Read};
+///
+/// fn drain(mut reader: impl Read) -> String {
+///     let mut contents = String::new();
+///     reader.read_to_string(&mut contents).unwrap();
+///     contents
+/// }
+///
+/// let mut input = Some(Cursor::new("hello"));
+///
+/// assert_eq!(drain(input.steal_mut()), "hello");
+/// assert_eq!(input.unwrap().position(), 5);
+/// ```
+#[cfg(feature = "std")]
+impl<'a, T: Read> Read for OptionGuardMut<'a, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
 
-        assert_eq!(thing, None);
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut]) -> io::Result<usize> {
+        (**self).read_vectored(bufs)
     }
 
-    #[test]
-    #[should_panic]
-    fn mut_from_none() {
-        let mut thing: Option<i32> = None;
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (**self).read_to_end(buf)
+    }
+
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        (**self).read_to_string(buf)
+    }
+
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read_exact(buf)
+    }
+}
+
+
+#[cfg(feature = "std")]
+impl<'a, T: BufRead> BufRead for OptionGuardMut<'a, T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        (**self).fill_buf()
+    }
+
+
+    fn consume(&mut self, amt: usize) {
+        (**self).consume(amt)
+    }
+
+
+    fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (**self).read_until(byte, buf)
+    }
+
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        (**self).read_line(buf)
+    }
+}
+
+
+#[cfg(feature = "std")]
+impl<'a, T: Write> Write for OptionGuardMut<'a, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        (**self).write_vectored(bufs)
+    }
+
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all(buf)
+    }
+
+
+    fn write_fmt(&mut self, args: fmt::Arguments) -> io::Result<()> {
+        (**self).write_fmt(args)
+    }
+}
+
+
+/// A stolen future can be polled through its guard, as long as it is `Unpin`: the guard moves the
+/// value back into the `Option` when dropped, so it cannot keep a `!Unpin` future pinned in place.
+/// Steal those with `PinOptionExt::steal_pin_mut` instead.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// use std::future::{self, Future};
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll, Waker};
+///
+/// let mut pending = Some(future::ready(5));
+///
+/// let mut cx = Context::from_waker(Waker::noop());
+/// assert_eq!(Pin::new(&mut pending.steal_mut()).poll(&mut cx), Poll::Ready(5));
+/// ```
+impl<'a, T: Future + Unpin> Future for OptionGuardMut<'a, T> {
+    type Output = T::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<T::Output> {
+        Pin::new(&mut **self.get_mut()).poll(cx)
+    }
+}
+
+
+impl<'a, T> AsRef<T> for OptionGuardMut<'a, T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+
+impl<'a, T> AsMut<T> for OptionGuardMut<'a, T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+
+impl<'a, T> Borrow<T> for OptionGuardMut<'a, T> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+
+impl<'a, T> BorrowMut<T> for OptionGuardMut<'a, T> {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+
+impl<'a, T: PartialEq> PartialEq<T> for OptionGuardMut<'a, T> {
+    fn eq(&self, other: &T) -> bool {
+        **self == *other
+    }
+}
+
+
+impl<'a, 'b, T: PartialEq> PartialEq<OptionGuardMut<'b, T>> for OptionGuardMut<'a, T> {
+    fn eq(&self, other: &OptionGuardMut<'b, T>) -> bool {
+        **self == **other
+    }
+}
+
+
+impl<'a, T: Eq> Eq for OptionGuardMut<'a, T> {}
+
+
+impl<'a, 'b, T: PartialOrd> PartialOrd<OptionGuardMut<'b, T>> for OptionGuardMut<'a, T> {
+    fn partial_cmp(&self, other: &OptionGuardMut<'b, T>) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+
+impl<'a, T: Ord> Ord for OptionGuardMut<'a, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+
+impl<'a, T: Hash> Hash for OptionGuardMut<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+
+impl<'a, T> Deref for OptionGuardMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for OptionGuardMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.mutated = true;
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+/// A read-only view of a value taken from an `Option<T>`, produced by `OptionGuardMut::freeze`.
+/// `OptionGuardRef<T>` dereferences to a `&T`, and when dropped, moves the taken value back into
+/// the `Option` it came from. Unlike `OptionGuardMut`, it allows neither mutating nor consuming
+/// the value, so it can be handed to helpers which should only be able to look.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{EmptyOptionExt, OptionGuardRef};
+/// fn describe(guard: &OptionGuardRef<Vec<i32>>) -> usize {
+///     guard.len()
+/// }
+///
+/// let mut thing = Some(vec![1, 2]);
+///
+/// {
+///     let mut stolen = thing.steal_mut();
+///
+///     stolen.push(3);
+///
+///     let frozen = stolen.freeze();
+///
+///     assert_eq!(describe(&frozen), 3);
+/// }
+///
+/// assert_eq!(thing, Some(vec![1, 2, 3]));
+/// ```
+pub struct OptionGuardRef<'a, T: 'a> {
+    inner: OptionGuardMut<'a, T>,
+}
+
+
+impl<'a, T> Deref for OptionGuardRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+
+/// An `OptionGuardMut` whose `Debug` impl leaves out the value, produced by
+/// `OptionGuardMut::opaque`. `OptionGuardMut<T>` is only `Debug` when `T` is, so that it can show
+/// the value; wrapping it lets a guard over a value which is not `Debug` be kept in a struct which
+/// derives `Debug`. It dereferences to the value and returns it when dropped, just like the guard
+/// it wraps.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{EmptyOptionExt, OpaqueGuardMut};
+/// struct Socket;
+///
+/// #[derive(Debug)]
+/// struct Connection<'a> {
+///     socket: OpaqueGuardMut<'a, Socket>,
+/// }
+///
+/// let mut socket = Some(Socket);
+/// let connection = Connection { socket: socket.steal_mut().opaque() };
+///
+/// assert_eq!(
+///     format!("{:?}", connection),
+///     "Connection { socket: OptionGuardMut { mutated: false, .. } }",
+/// );
+/// ```
+pub struct OpaqueGuardMut<'a, T: 'a> {
+    inner: OptionGuardMut<'a, T>,
+}
+
+
+impl<'a, T> OpaqueGuardMut<'a, T> {
+    /// Unwrap the guard, to get at its `Debug` impl or its other methods.
+    pub fn into_guard_mut(self) -> OptionGuardMut<'a, T> {
+        self.inner
+    }
+}
+
+
+impl<'a, T> fmt::Debug for OpaqueGuardMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OptionGuardMut")
+            .field("mutated", &self.inner.mutated)
+            .finish_non_exhaustive()
+    }
+}
+
+
+impl<'a, T> Deref for OpaqueGuardMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+
+impl<'a, T> DerefMut for OpaqueGuardMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+
+/// A value taken from an `Option<T>` by `EmptyOptionExt::steal_deref`. `OptionGuardDeref<T>`
+/// dereferences to the target of the stolen `T` rather than to the `T` itself, so an
+/// `Option<String>` can be worked on as a `str` and an `Option<Vec<u8>>` as a `[u8]`. When
+/// dropped, it moves the taken value back into the `Option` it came from.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// let mut thing = Some(String::from("hello"));
+///
+/// {
+///     let mut stolen = thing.steal_deref();
+///
+///     stolen.make_ascii_uppercase();
+///
+///     assert_eq!(&*stolen, "HELLO");
+/// }
+///
+/// assert_eq!(thing, Some(String::from("HELLO")));
+/// ```
+pub struct OptionGuardDeref<'a, T: 'a> {
+    inner: OptionGuardMut<'a, T>,
+}
+
+
+impl<'a, T> OptionGuardDeref<'a, T> {
+    /// Keep the value stolen from the `Option` and do not return it.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+
+impl<'a, T: Deref> Deref for OptionGuardDeref<'a, T> {
+    type Target = T::Target;
+
+    fn deref(&self) -> &T::Target {
+        &self.inner
+    }
+}
+
+
+impl<'a, T: DerefMut> DerefMut for OptionGuardDeref<'a, T> {
+    fn deref_mut(&mut self) -> &mut T::Target {
+        &mut self.inner
+    }
+}
+
+
+impl<T> EmptyOptionExt for Option<T> {
+    type Inner = T;
+
+    #[track_caller]
+    fn steal<'a>(&'a mut self) -> (OptionGuard<'a, T>, T) {
+        let value = match self.take() {
+            Some(value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        };
+
+        (OptionGuard::new(self), value)
+    }
+
+    #[track_caller]
+    fn steal_with_policy<'a, P>(&'a mut self) -> (OptionGuard<'a, T, P>, T)
+    where
+        P: OnLeak<T>,
+    {
+        let value = match self.take() {
+            Some(value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        };
+
+        (OptionGuard::new(self), value)
+    }
+
+    #[track_caller]
+    fn steal_with_context<'a>(&'a mut self, context: &'static str) -> (OptionGuard<'a, T>, T) {
+        let value = match self.take() {
+            Some(value) => value,
+            None => violation::steal_from_none_with::<T, _>(self, context),
+        };
+
+        let mut guard = OptionGuard::new(self);
+        guard.context = Some(context);
+
+        (guard, value)
+    }
+
+    #[track_caller]
+    fn steal_with_fallback<'a>(&'a mut self, fallback: T) -> (FallbackGuard<'a, T>, T) {
+        let value = match self.take() {
+            Some(value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        };
+
+        (FallbackGuard::new(self, fallback), value)
+    }
+
+    #[track_caller]
+    fn steal_replace<'a>(&'a mut self, placeholder: T) -> (SentinelGuard<'a, T>, T) {
+        let value = match self.replace(placeholder) {
+            Some(value) => value,
+            None => {
+                *self = None;
+                violation::steal_from_none::<T, _>(self)
+            }
+        };
+
+        (SentinelGuard::new(self), value)
+    }
+
+    #[track_caller]
+    fn swap_steal<'a>(&'a mut self, other: &'a mut Option<T>) -> (SwapGuard<'a, T>, T, T) {
+        if self.is_none() {
+            violation::steal_from_none::<T, _>(self);
+        }
+
+        let (first, second) = match other.take() {
+            Some(second) => (self.take().unwrap(), second),
+            None => violation::steal_from_none::<T, _>(other),
+        };
+
+        (SwapGuard::new(self, other), first, second)
+    }
+
+    #[track_caller]
+    fn steal_strict<'a>(&'a mut self) -> (OptionGuard<'a, T, Abort>, T) {
+        let value = match self.take() {
+            Some(value) => value,
+            None => violation::steal_from_none_abort::<T, _>(self),
+        };
+
+        (OptionGuard::new(self), value)
+    }
+
+    #[track_caller]
+    fn try_steal_strict<'a>(&'a mut self) -> Result<(OptionGuard<'a, T, Abort>, T), StealError> {
+        let value = self.take().ok_or(StealError)?;
+
+        Ok((OptionGuard::new(self), value))
+    }
+
+    #[track_caller]
+    fn try_steal<'a>(&'a mut self) -> Result<(OptionGuard<'a, T>, T), StealError> {
+        let value = self.take().ok_or(StealError)?;
+
+        Ok((OptionGuard::new(self), value))
+    }
+
+    #[track_caller]
+    fn steal_mut<'a>(&'a mut self) -> OptionGuardMut<'a, T> {
+        let value = match self.take() {
+            Some(value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        };
+
+        unsafe { OptionGuardMut::from_parts(self, value) }
+    }
+
+    #[track_caller]
+    fn try_steal_mut<'a>(&'a mut self) -> Result<OptionGuardMut<'a, T>, StealError> {
+        let value = self.take().ok_or(StealError)?;
+
+        Ok(unsafe { OptionGuardMut::from_parts(self, value) })
+    }
+
+    #[track_caller]
+    fn steal_mut_or_default<'a>(&'a mut self) -> OptionGuardMut<'a, T>
+    where
+        T: Default,
+    {
+        let value = self.take().unwrap_or_default();
+
+        unsafe { OptionGuardMut::from_parts(self, value) }
+    }
+
+    #[track_caller]
+    fn steal_or_insert_with<'a, F>(&'a mut self, f: F) -> (OptionGuard<'a, T>, T)
+    where
+        F: FnOnce() -> T,
+    {
+        let value = self.take().unwrap_or_else(f);
+
+        (OptionGuard::new(self), value)
+    }
+
+    #[track_caller]
+    fn steal_mut_or_insert_with<'a, F>(&'a mut self, f: F) -> OptionGuardMut<'a, T>
+    where
+        F: FnOnce() -> T,
+    {
+        let value = self.take().unwrap_or_else(f);
+
+        unsafe { OptionGuardMut::from_parts(self, value) }
+    }
+
+    #[track_caller]
+    fn steal_deref<'a>(&'a mut self) -> OptionGuardDeref<'a, T>
+    where
+        T: DerefMut,
+    {
+        OptionGuardDeref {
+            inner: self.steal_mut(),
+        }
+    }
+
+    fn guard_entry<'a>(&'a mut self) -> GuardEntry<'a, T> {
+        GuardEntry::new(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catch_and_release() {
+        let mut thing = Some(5);
+
+        {
+            let (guard, five) = thing.steal();
+
+            assert_eq!(five, 5);
+
+            guard.restore(6);
+        }
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    fn defuse_leaves_none() {
+        let mut thing = Some(5);
+
+        let five = {
+            let (guard, five) = thing.steal();
+            guard.defuse();
+            five
+        };
+
+        assert_eq!((five, thing), (5, None));
+    }
+
+    #[test]
+    fn restore_with_closure() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut thing = Some(5);
+
+        {
+            let (guard, five) = thing.steal();
+            guard.restore_with(|| five * 2);
+        }
+
+        assert_eq!(thing, Some(10));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (guard, _) = thing.steal();
+            guard.restore_with(|| panic!("expensive replacement failed"));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(thing, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn catch_and_keep() {
+        let mut thing = Some(5);
+
+        let (_, _) = thing.steal();
+
+        // Never return the value!
+    }
+
+    #[test]
+    #[should_panic]
+    fn catch_from_none() {
+        let mut thing = None;
+
+        let (guard, _) = thing.steal();
+
+        guard.restore(5);
+    }
+
+    #[test]
+    fn try_catch_from_none() {
+        let mut thing: Option<i32> = None;
+
+        assert_eq!(thing.try_steal().err(), Some(StealError));
+
+        thing = Some(5);
+        let (guard, five) = thing.try_steal().unwrap();
+        guard.restore(five + 1);
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    fn mut_and_release() {
+        let mut thing = Some(5);
+
+        {
+            let mut stolen = thing.steal_mut();
+
+            assert_eq!(*stolen, 5);
+
+            *stolen = 6;
+        }
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    fn mut_and_keep() {
+        let mut thing = Some(5);
+        
+        {
+            // Keep the thing!
+            let stolen = thing.steal_mut().into_inner();
+
+            assert_eq!(stolen, 5);
+        }
+
+        assert_eq!(thing, None);
+    }
+
+    #[test]
+    fn mut_and_replace() {
+        let mut state = Some("idle");
+
+        {
+            let mut stolen = state.steal_mut();
+
+            assert_eq!(stolen.replace("running"), "idle");
+            assert_eq!(stolen.replace("done"), "running");
+        }
+
+        assert_eq!(state, Some("done"));
+    }
+
+    #[test]
+    fn mut_and_map() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut thing = Some(vec![1]);
+
+        {
+            let stolen = thing.steal_mut().map(|mut v| {
+                v.push(2);
+                v
+            });
+
+            assert_eq!(*stolen, vec![1, 2]);
+        }
+
+        assert_eq!(thing, Some(vec![1, 2]));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            thing.steal_mut().map(|_| panic!("transition failed"));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(thing, None);
+    }
+
+    #[test]
+    fn mut_into_mut() {
+        let mut thing = Some(5);
+
+        let five = thing.steal_mut().into_mut();
+        *five += 1;
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    fn restore_from_none() {
+        let mut thing = Some(1);
+        let mut empty = None;
+
+        let (guard, one) = thing.steal();
+        let guard = guard.restore_from(&mut empty).unwrap_err();
+        guard.restore(one);
+
+        assert_eq!(thing, Some(1));
+    }
+
+    #[test]
+    fn format_guards() {
+        struct Opaque;
+
+        let mut opaque = Some(Opaque);
+        let (guard, value) = opaque.steal_with_context("debugging");
+        assert!(format!("{:?}", guard).contains("debugging"));
+        guard.restore(value);
+
+        let mut stolen = opaque.steal_mut().opaque();
+        *stolen = Opaque;
+        assert!(format!("{:?}", stolen).contains("mutated: true"));
+        drop(stolen);
+        assert!(opaque.is_some());
+
+        let mut thing = Some(5);
+        assert!(format!("{:?}", thing.steal_mut()).contains("value: 5"));
+        assert_eq!(format!("{:>3}", thing.steal_mut()), "  5");
+    }
+
+    #[test]
+    fn compare_guards() {
+        let mut low = Some(1);
+        let mut high = Some(2);
+
+        let low = low.steal_mut();
+        let high = high.steal_mut();
+
+        assert!(low == 1 && low != high);
+        assert!(low < high);
+        assert_eq!(low.cmp(&low), Ordering::Equal);
+    }
+
+    #[test]
+    fn reference_conversions() {
+        fn push<V: AsMut<Vec<i32>>>(mut v: V) {
+            v.as_mut().push(2);
+        }
+
+        let mut thing = Some(vec![1]);
+        let stolen = thing.steal_mut();
+
+        assert_eq!(Borrow::<Vec<i32>>::borrow(&stolen).len(), 1);
+        push(stolen);
+
+        assert_eq!(thing, Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn guards_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<OptionGuard<'static, String>>();
+        assert_send_sync::<OptionGuardMut<'static, String>>();
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_and_read_lines() {
+        let mut output = Some(Vec::new());
+        writeln!(output.steal_mut(), "one\ntwo").unwrap();
+
+        let mut input = Some(io::Cursor::new(output.unwrap()));
+        let lines: Vec<_> = input.steal_mut().lines().map(Result::unwrap).collect();
+
+        assert_eq!(lines, ["one", "two"]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn transfer_to_occupied() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut from = Some(1);
+        let mut to = Some(2);
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (guard, _) = from.steal_with_context("moving to the busy list");
+            drop(guard.transfer(&mut to));
+        }))
+        .unwrap_err();
+
+        let occupied = payload.downcast_ref::<TransferToOccupied>().unwrap();
+
+        assert!(occupied.is_slot(&to));
+        assert_eq!(occupied.context(), Some("moving to the busy list"));
+        assert_eq!((from, to), (None, Some(2)));
+    }
+
+    #[test]
+    fn mut_and_discard() {
+        let mut thing = Some(5);
+
+        thing.steal_mut().discard();
+
+        assert_eq!(thing, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mut_from_none() {
+        let mut thing: Option<i32> = None;
 
         thing.steal_mut();
     }
+
+    #[test]
+    fn try_mut_from_none() {
+        let mut thing: Option<i32> = None;
+
+        assert!(thing.try_steal_mut().is_err());
+
+        thing = Some(5);
+        *thing.try_steal_mut().unwrap() += 1;
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    fn mut_or_default() {
+        let mut counts: Option<Vec<u32>> = None;
+
+        counts.steal_mut_or_default().push(1);
+        counts.steal_mut_or_default().push(2);
+
+        assert_eq!(counts, Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn or_insert_with() {
+        let mut state = None;
+
+        {
+            let (guard, state_value) = state.steal_or_insert_with(|| 1);
+            guard.restore(state_value + 1);
+        }
+
+        *state.steal_mut_or_insert_with(|| unreachable!()) += 1;
+
+        assert_eq!(state, Some(3));
+    }
+
+    #[test]
+    fn mut_and_freeze() {
+        let mut thing = Some(5);
+
+        {
+            let mut stolen = thing.steal_mut();
+
+            *stolen = 6;
+
+            let frozen = stolen.freeze();
+
+            assert_eq!(*frozen, 6);
+        }
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn typed_panic_payloads() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut thing = Some(5);
+
+        let stolen_at = line!() + 2;
+        let leaked = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (_, _) = thing.steal();
+        })).unwrap_err();
+
+        let leaked = leaked.downcast_ref::<GuardLeaked>().unwrap();
+        assert!(leaked.is_slot(&thing));
+        assert_eq!(leaked.location().unwrap().file(), file!());
+        assert_eq!(leaked.location().unwrap().line(), stolen_at);
+
+        let from_none = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (guard, _) = thing.steal();
+            guard.restore(6);
+        })).unwrap_err();
+
+        let from_none = from_none.downcast_ref::<StealFromNone>().unwrap();
+        assert!(from_none.is_slot(&thing));
+        assert_eq!(from_none.type_name(), "i32");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn context_in_panics() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut thing = Some(5);
+
+        let leaked = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (_, _) = thing.steal_with_context("parsing the header");
+        })).unwrap_err();
+
+        let leaked = leaked.downcast_ref::<GuardLeaked>().unwrap();
+        assert_eq!(leaked.context(), Some("parsing the header"));
+        assert!(leaked.to_string().ends_with("): parsing the header"));
+
+        let from_none = panic::catch_unwind(AssertUnwindSafe(|| {
+            thing.steal_with_context("parsing the body");
+        })).unwrap_err();
+
+        let from_none = from_none.downcast_ref::<StealFromNone>().unwrap();
+        assert_eq!(from_none.context(), Some("parsing the body"));
+    }
+
+    #[test]
+    fn deref_through_to_target() {
+        let mut thing = Some(vec![3, 1, 2]);
+
+        {
+            let mut stolen = thing.steal_deref();
+
+            stolen.sort();
+
+            assert_eq!(stolen.len(), 3);
+        }
+
+        assert_eq!(thing, Some(vec![1, 2, 3]));
+        assert_eq!(thing.steal_deref().into_inner(), vec![1, 2, 3]);
+        assert_eq!(thing, None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn unwind_safety() {
+        use std::panic::{RefUnwindSafe, UnwindSafe};
+
+        fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+
+        assert_unwind_safe::<SharedSlot<i32>>();
+        assert_unwind_safe::<SharedSlotGuard<i32>>();
+        assert_unwind_safe::<AsyncSlot<i32>>();
+        assert_unwind_safe::<AsyncSlotGuard<i32>>();
+        assert_unwind_safe::<OwnedAsyncSlotGuard<i32>>();
+        assert_unwind_safe::<LeaseSlot<i32>>();
+        assert_unwind_safe::<LeaseGuard<i32>>();
+        assert_unwind_safe::<Pool<i32>>();
+        assert_unwind_safe::<PoolGuard<i32>>();
+        assert_unwind_safe::<SlotRing<i32>>();
+        #[cfg(target_has_atomic = "64")]
+        assert_unwind_safe::<AtomicSmallSlot<i32>>();
+    }
+
+    #[test]
+    fn guards_from_and_into_parts() {
+        let mut thing = Some(5);
+
+        {
+            let (guard, five) = thing.steal();
+            let slot = unsafe { guard.into_parts() };
+
+            assert_eq!(*slot, None);
+
+            let guard = unsafe { OptionGuard::from_parts(slot) };
+            guard.restore(five + 1);
+        }
+
+        {
+            let (slot, six) = unsafe { thing.steal_mut().into_parts() };
+
+            assert_eq!(*slot, None);
+
+            let mut guard = unsafe { OptionGuardMut::from_parts(slot, six) };
+            *guard += 1;
+        }
+
+        assert_eq!(thing, Some(7));
+    }
 }
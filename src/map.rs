@@ -0,0 +1,595 @@
+use alloc::collections::BTreeMap;
+use std::borrow::Borrow;
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "std")]
+use std::ptr;
+
+#[cfg(feature = "std")]
+use {EmptyOptionExt, OptionGuardMut};
+
+
+/// Extension trait providing `for_each_stolen` and `steal_entry` on `HashMap`s.
+#[cfg(feature = "std")]
+pub trait HashMapStealExt {
+    type Key: Eq + Hash;
+    type Value;
+    type Hasher: BuildHasher;
+
+    /// Visit every entry of the map, handing `f` the entry's key and an `OptionGuardMut` over its
+    /// value. Each value is put back into the map as soon as its guard is dropped; consuming the
+    /// guard with `OptionGuardMut::into_inner` removes the entry instead. If `f` panics, every
+    /// entry (including the one being visited) is still put back.
+    ///
+    /// The entries are visited in place: no key is hashed again, and the map keeps its hasher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::HashMapStealExt;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut versions = HashMap::new();
+    /// versions.insert("a", String::from("v1:hello"));
+    /// versions.insert("b", String::from("v1:bye"));
+    /// versions.insert("c", String::from("retired"));
+    ///
+    /// versions.for_each_stolen(|_, mut value| {
+    ///     if *value == "retired" {
+    ///         // Drop the entry altogether.
+    ///         value.into_inner();
+    ///     } else {
+    ///         // Migrate the value by-value, without re-looking-up its key.
+    ///         *value = (*value).replace("v1:", "v2:");
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(versions.len(), 2);
+    /// assert_eq!(versions["a"], "v2:hello");
+    /// ```
+    fn for_each_stolen<F>(&mut self, f: F)
+    where
+        F: for<'a> FnMut(&'a Self::Key, OptionGuardMut<'a, Self::Value>);
+
+    /// Remove the entry for `key`, providing a guard which owns its value and reinserts it under
+    /// the same key when dropped, unless consumed by `HashMapEntryGuard::into_inner`. Returns
+    /// `None` if the map has no entry for `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::HashMapStealExt;
+    /// use std::collections::HashMap;
+    ///
+    /// fn rename(users: &mut HashMap<u32, String>, id: u32, name: &str) -> Result<(), String> {
+    ///     let mut user = users.steal_entry(&id).ok_or("no such user")?;
+    ///
+    ///     if name.is_empty() {
+    ///         // The early return puts the user back.
+    ///         return Err(String::from("empty name"));
+    ///     }
+    ///
+    ///     *user = String::from(name);
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut users = HashMap::new();
+    /// users.insert(1, String::from("alice"));
+    ///
+    /// assert!(rename(&mut users, 1, "").is_err());
+    /// assert_eq!(users[&1], "alice");
+    ///
+    /// assert!(rename(&mut users, 1, "bob").is_ok());
+    /// assert_eq!(users[&1], "bob");
+    /// ```
+    fn steal_entry<'a, Q>(
+        &'a mut self,
+        key: &Q,
+    ) -> Option<HashMapEntryGuard<'a, Self::Key, Self::Value, Self::Hasher>>
+    where
+        Self::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized;
+}
+
+
+#[cfg(feature = "std")]
+impl<K, V, S> HashMapStealExt for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    type Key = K;
+    type Value = V;
+    type Hasher = S;
+
+    fn for_each_stolen<F>(&mut self, mut f: F)
+    where
+        F: for<'a> FnMut(&'a K, OptionGuardMut<'a, V>),
+    {
+        let mut panicked = None;
+
+        // Each value is moved out of the map into a local `Option` for `f` to steal from, and
+        // moved back in place afterwards, so no key is hashed again. A value `f` consumes is left
+        // behind as a stale copy, which `extract_if` hands back to be forgotten rather than
+        // dropped. A panic in `f` is held until every value is back in the map.
+        let consumed = self.extract_if(|key, value| {
+            if panicked.is_some() {
+                return false;
+            }
+
+            let mut slot = Some(unsafe { ptr::read(value) });
+
+            let visit = panic::catch_unwind(AssertUnwindSafe(|| f(key, slot.steal_mut())));
+
+            if let Err(payload) = visit {
+                panicked = Some(payload);
+            }
+
+            match slot {
+                Some(returned) => {
+                    unsafe { ptr::write(value, returned) };
+                    false
+                }
+                None => true,
+            }
+        });
+
+        for (_, stale) in consumed {
+            mem::forget(stale);
+        }
+
+        if let Some(payload) = panicked {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    fn steal_entry<'a, Q>(&'a mut self, key: &Q) -> Option<HashMapEntryGuard<'a, K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.remove_entry(key)?;
+
+        Some(HashMapEntryGuard {
+            map: self,
+            entry: Some(entry),
+        })
+    }
+}
+
+
+/// An entry taken from a `HashMap` by `HashMapStealExt::steal_entry`. `HashMapEntryGuard<K, V>`
+/// dereferences to the entry's value, which can be moved out with `HashMapEntryGuard::into_inner`.
+/// When dropped, the `HashMapEntryGuard` reinserts the entry under its original key.
+#[cfg(feature = "std")]
+pub struct HashMapEntryGuard<'a, K: 'a + Eq + Hash, V: 'a, S: 'a + BuildHasher = RandomState> {
+    map: &'a mut HashMap<K, V, S>,
+    entry: Option<(K, V)>,
+}
+
+
+#[cfg(feature = "std")]
+impl<'a, K: Eq + Hash, V, S: BuildHasher> HashMapEntryGuard<'a, K, V, S> {
+    /// The key the entry was stored under.
+    pub fn key(&self) -> &K {
+        &self.entry.as_ref().unwrap().0
+    }
+
+
+    /// Keep the value taken from the map, leaving the entry removed.
+    pub fn into_inner(mut self) -> V {
+        self.entry.take().unwrap().1
+    }
+}
+
+
+#[cfg(feature = "std")]
+impl<'a, K: Eq + Hash, V, S: BuildHasher> Drop for HashMapEntryGuard<'a, K, V, S> {
+    fn drop(&mut self) {
+        if let Some((key, value)) = self.entry.take() {
+            self.map.insert(key, value);
+        }
+    }
+}
+
+
+#[cfg(feature = "std")]
+impl<'a, K: Eq + Hash, V, S: BuildHasher> Deref for HashMapEntryGuard<'a, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        &self.entry.as_ref().unwrap().1
+    }
+}
+
+
+#[cfg(feature = "std")]
+impl<'a, K: Eq + Hash, V, S: BuildHasher> DerefMut for HashMapEntryGuard<'a, K, V, S> {
+    fn deref_mut(&mut self) -> &mut V {
+        &mut self.entry.as_mut().unwrap().1
+    }
+}
+
+
+/// Extension trait providing `steal_range` and `steal_entry` on `BTreeMap`s.
+pub trait BTreeMapStealExt {
+    type Key: Ord;
+    type Value;
+
+    /// Remove every entry with a key in `range`, providing a guard which owns them as a
+    /// `BTreeMap` of their own, and splices them back into this map when dropped unless consumed
+    /// by `RangeGuard::into_inner`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::BTreeMapStealExt;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut readings: BTreeMap<u32, f64> = (0..10).map(|t| (t, t as f64)).collect();
+    ///
+    /// {
+    ///     let mut window = readings.steal_range(3..6);
+    ///
+    ///     assert_eq!(window.keys().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    ///
+    ///     for value in window.values_mut() {
+    ///         *value *= 2.0;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(readings.len(), 10);
+    /// assert_eq!(readings[&4], 8.0);
+    ///
+    /// // Consume a window to remove it for good.
+    /// assert_eq!(readings.steal_range(..2).into_inner().len(), 2);
+    /// assert_eq!(readings.len(), 8);
+    /// ```
+    fn steal_range<'a, R>(&'a mut self, range: R) -> RangeGuard<'a, Self::Key, Self::Value>
+    where
+        R: RangeBounds<Self::Key>;
+
+    /// Remove the entry for `key`, providing a guard which owns its value and reinserts it under
+    /// the same key when dropped, unless consumed by `BTreeMapEntryGuard::into_inner`. Returns
+    /// `None` if the map has no entry for `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::BTreeMapStealExt;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut queue: BTreeMap<u32, Vec<&str>> = BTreeMap::new();
+    /// queue.insert(1, vec!["low"]);
+    /// queue.insert(9, vec!["high"]);
+    ///
+    /// {
+    ///     let mut urgent = queue.steal_entry(&9).unwrap();
+    ///
+    ///     urgent.push("higher");
+    /// }
+    ///
+    /// assert_eq!(queue.keys().copied().collect::<Vec<_>>(), vec![1, 9]);
+    /// assert_eq!(queue[&9], ["high", "higher"]);
+    /// ```
+    fn steal_entry<'a, Q>(
+        &'a mut self,
+        key: &Q,
+    ) -> Option<BTreeMapEntryGuard<'a, Self::Key, Self::Value>>
+    where
+        Self::Key: Borrow<Q>,
+        Q: Ord + ?Sized;
+}
+
+
+impl<K: Ord, V> BTreeMapStealExt for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn steal_range<'a, R>(&'a mut self, range: R) -> RangeGuard<'a, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        let mut stolen = match range.start_bound() {
+            Bound::Included(start) => self.split_off(start),
+            Bound::Excluded(start) => {
+                let mut tail = self.split_off(start);
+
+                if let Some((key, value)) = tail.remove_entry(start) {
+                    self.insert(key, value);
+                }
+
+                tail
+            }
+            Bound::Unbounded => mem::take(self),
+        };
+
+        let mut rest = match range.end_bound() {
+            Bound::Included(end) => {
+                let mut rest = stolen.split_off(end);
+
+                if let Some((key, value)) = rest.remove_entry(end) {
+                    stolen.insert(key, value);
+                }
+
+                rest
+            }
+            Bound::Excluded(end) => stolen.split_off(end),
+            Bound::Unbounded => BTreeMap::new(),
+        };
+
+        self.append(&mut rest);
+
+        RangeGuard {
+            origin: self,
+            stolen,
+        }
+    }
+
+    fn steal_entry<'a, Q>(&'a mut self, key: &Q) -> Option<BTreeMapEntryGuard<'a, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let entry = self.remove_entry(key)?;
+
+        Some(BTreeMapEntryGuard {
+            map: self,
+            entry: Some(entry),
+        })
+    }
+}
+
+
+/// A range of entries taken from a `BTreeMap` by `BTreeMapStealExt::steal_range`.
+/// `RangeGuard<K, V>` dereferences to a `BTreeMap<K, V>` holding the entries, which can be moved
+/// out with `RangeGuard::into_inner`. When dropped, the `RangeGuard` splices the entries (along
+/// with any added to it in the meantime) back into the map they came from.
+pub struct RangeGuard<'a, K: 'a + Ord, V: 'a> {
+    origin: &'a mut BTreeMap<K, V>,
+    stolen: BTreeMap<K, V>,
+}
+
+
+impl<'a, K: Ord, V> RangeGuard<'a, K, V> {
+    /// Keep the entries taken from the map and do not return them.
+    pub fn into_inner(mut self) -> BTreeMap<K, V> {
+        mem::take(&mut self.stolen)
+    }
+}
+
+
+impl<'a, K: Ord, V> Drop for RangeGuard<'a, K, V> {
+    fn drop(&mut self) {
+        self.origin.append(&mut self.stolen);
+    }
+}
+
+
+impl<'a, K: Ord, V> Deref for RangeGuard<'a, K, V> {
+    type Target = BTreeMap<K, V>;
+
+    fn deref(&self) -> &BTreeMap<K, V> {
+        &self.stolen
+    }
+}
+
+
+impl<'a, K: Ord, V> DerefMut for RangeGuard<'a, K, V> {
+    fn deref_mut(&mut self) -> &mut BTreeMap<K, V> {
+        &mut self.stolen
+    }
+}
+
+
+/// An entry taken from a `BTreeMap` by `BTreeMapStealExt::steal_entry`. `BTreeMapEntryGuard<K, V>`
+/// dereferences to the entry's value, which can be moved out with `BTreeMapEntryGuard::into_inner`.
+/// When dropped, the `BTreeMapEntryGuard` reinserts the entry under its original key - and so in
+/// its original position, since the key cannot be changed through the guard.
+pub struct BTreeMapEntryGuard<'a, K: 'a + Ord, V: 'a> {
+    map: &'a mut BTreeMap<K, V>,
+    entry: Option<(K, V)>,
+}
+
+
+impl<'a, K: Ord, V> BTreeMapEntryGuard<'a, K, V> {
+    /// The key the entry was stored under.
+    pub fn key(&self) -> &K {
+        &self.entry.as_ref().unwrap().0
+    }
+
+
+    /// Keep the value taken from the map, leaving the entry removed.
+    pub fn into_inner(mut self) -> V {
+        self.entry.take().unwrap().1
+    }
+}
+
+
+impl<'a, K: Ord, V> Drop for BTreeMapEntryGuard<'a, K, V> {
+    fn drop(&mut self) {
+        if let Some((key, value)) = self.entry.take() {
+            self.map.insert(key, value);
+        }
+    }
+}
+
+
+impl<'a, K: Ord, V> Deref for BTreeMapEntryGuard<'a, K, V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        &self.entry.as_ref().unwrap().1
+    }
+}
+
+
+impl<'a, K: Ord, V> DerefMut for BTreeMapEntryGuard<'a, K, V> {
+    fn deref_mut(&mut self) -> &mut V {
+        &mut self.entry.as_mut().unwrap().1
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    use std::collections::hash_map::DefaultHasher;
+    #[cfg(feature = "std")]
+    use std::hash::Hasher;
+
+    /// A hasher with no `Default`, seeded per map.
+    #[cfg(feature = "std")]
+    struct Seeded(u64);
+
+    #[cfg(feature = "std")]
+    impl BuildHasher for Seeded {
+        type Hasher = DefaultHasher;
+
+        fn build_hasher(&self) -> DefaultHasher {
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(self.0);
+            hasher
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn visits_every_entry() {
+        let mut map: HashMap<_, _> = (0..10).map(|i| (i, i * 10)).collect();
+        let mut visited = 0;
+
+        map.for_each_stolen(|&key, mut value| {
+            assert_eq!(*value, key * 10);
+            *value += 1;
+            visited += 1;
+        });
+
+        assert_eq!(visited, 10);
+        assert!(map.iter().all(|(&key, &value)| value == key * 10 + 1));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn panic_keeps_entries() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut map: HashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            map.for_each_stolen(|&key, _| {
+                if key == 5 {
+                    panic!("oops");
+                }
+            });
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(map.len(), 10);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn consume_with_seeded_hasher() {
+        use std::rc::Rc;
+
+        let value = Rc::new(());
+        let mut map = HashMap::with_hasher(Seeded(7));
+        map.extend((0..10).map(|i| (i, value.clone())));
+
+        map.for_each_stolen(|&key, value| {
+            if key % 2 == 0 {
+                value.into_inner();
+            }
+        });
+
+        assert_eq!(map.hasher().0, 7);
+        assert_eq!(map.len(), 5);
+        assert!((0..10).all(|key| map.contains_key(&key) == (key % 2 == 1)));
+
+        // Consumed values were dropped exactly once, and the rest are still owned by the map.
+        assert_eq!(Rc::strong_count(&value), 6);
+        drop(map);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn steal_entry_reinserts() {
+        let mut map: HashMap<_, _> = (0..3).map(|i| (i.to_string(), i)).collect();
+
+        assert!(map.steal_entry("7").is_none());
+
+        {
+            let mut entry = map.steal_entry("1").unwrap();
+
+            assert_eq!(entry.key(), "1");
+            *entry += 10;
+        }
+
+        assert_eq!(map["1"], 11);
+        assert_eq!(map.steal_entry("2").unwrap().into_inner(), 2);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn steal_entry_with_seeded_hasher() {
+        let mut map = HashMap::with_hasher(Seeded(3));
+        map.insert("key", 1);
+
+        *map.steal_entry("key").unwrap() += 1;
+
+        assert_eq!(map["key"], 2);
+        assert_eq!(map.steal_entry("key").unwrap().into_inner(), 2);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn steal_btree_entry() {
+        let mut map: BTreeMap<_, _> = (0..5).map(|i| (i, i)).collect();
+
+        assert!(map.steal_entry(&7).is_none());
+
+        {
+            let mut entry = map.steal_entry(&2).unwrap();
+
+            assert_eq!(*entry.key(), 2);
+            *entry = 20;
+        }
+
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), vec![0, 1, 20, 3, 4]);
+        assert_eq!(map.steal_entry(&4).unwrap().into_inner(), 4);
+        assert_eq!(map.len(), 4);
+    }
+
+    #[test]
+    fn range_bounds() {
+        let mut map: BTreeMap<_, _> = (0..10).map(|i| (i, i)).collect();
+
+        let keys = |guard: &RangeGuard<i32, i32>| guard.keys().copied().collect::<Vec<_>>();
+
+        assert_eq!(keys(&map.steal_range(2..=4)), vec![2, 3, 4]);
+        assert_eq!(keys(&map.steal_range((Bound::Excluded(7), Bound::Unbounded))), vec![8, 9]);
+        assert_eq!(keys(&map.steal_range(..)).len(), 10);
+
+        {
+            let guard = map.steal_range(3..5);
+
+            let rest: Vec<_> = guard.origin.keys().copied().collect();
+            assert_eq!(rest, vec![0, 1, 2, 5, 6, 7, 8, 9]);
+        }
+
+        assert_eq!(map.len(), 10);
+    }
+}
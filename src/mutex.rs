@@ -0,0 +1,294 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+
+use violation;
+
+
+/// Extension trait for locking a `Mutex<Option<T>>` and stealing its value in one step.
+///
+/// Code which already holds a `MutexGuard<Option<T>>` from its own locking logic doesn't need
+/// this: `EmptyOptionExt::steal` and `EmptyOptionExt::steal_mut` work straight through the lock
+/// guard, and the resulting option guard borrows the lock guard, so it cannot outlive the lock.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// use std::sync::Mutex;
+///
+/// let thing = Mutex::new(Some(5));
+///
+/// {
+///     let mut lock = thing.lock().unwrap();
+///
+///     let (guard, five) = lock.steal();
+///     guard.restore(five + 1);
+///
+///     *lock.steal_mut() += 1;
+/// }
+///
+/// assert_eq!(*thing.lock().unwrap(), Some(7));
+/// ```
+pub trait MutexOptionExt {
+    type Inner;
+
+    /// Lock the mutex and take its value, providing a guard which returns the value and unlocks
+    /// the mutex unless consumed by `MutexOptionGuard::into_inner`. Panics if the mutex is
+    /// poisoned or holds `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids; use `try_lock_steal`")
+    )]
+    fn lock_steal<'a>(&'a self) -> MutexOptionGuard<'a, Self::Inner>;
+
+    /// Like `lock_steal`, but handles a poisoned mutex according to `on_poison` instead of
+    /// panicking. Panics if the mutex holds `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids; use `try_lock_steal`")
+    )]
+    fn steal_or_recover<'a>(
+        &'a self,
+        on_poison: OnPoison,
+    ) -> Result<MutexOptionGuard<'a, Self::Inner>, Poisoned>;
+
+    /// Like `steal_or_recover`, but never panicking: returns `Ok(None)`, with the mutex unlocked,
+    /// if it holds `None`. Like `lock_steal`, this blocks until the mutex can be locked.
+    fn try_lock_steal<'a>(
+        &'a self,
+        on_poison: OnPoison,
+    ) -> Result<Option<MutexOptionGuard<'a, Self::Inner>>, Poisoned>;
+}
+
+
+/// What `MutexOptionExt::steal_or_recover` should do when the mutex is poisoned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OnPoison {
+    /// Ignore the poisoning, clear it, and steal the value anyway.
+    Recover,
+
+    /// Unlock the mutex and return a `Poisoned` error.
+    Fail,
+}
+
+
+/// The error returned by `MutexOptionExt::steal_or_recover` when the mutex is poisoned and
+/// `OnPoison::Fail` was requested.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Poisoned;
+
+
+impl fmt::Display for Poisoned {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("attempted to steal from a poisoned Mutex")
+    }
+}
+
+
+impl Error for Poisoned {}
+
+
+/// A value taken from a locked `Mutex<Option<T>>`. `MutexOptionGuard` keeps the mutex locked for
+/// as long as it lives and dereferences to the taken `T`; when dropped, it moves the value back
+/// into the mutex before unlocking it, unless the value was consumed with
+/// `MutexOptionGuard::into_inner`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{MutexOptionExt, OnPoison};
+/// use std::sync::Mutex;
+///
+/// let thing = Mutex::new(Some(5));
+///
+/// {
+///     let mut stolen = thing.lock_steal();
+///
+///     assert_eq!(*stolen, 5);
+///
+///     *stolen = 6;
+/// }
+///
+/// assert_eq!(*thing.lock().unwrap(), Some(6));
+///
+/// // A poisoned mutex can be treated as recoverable instead of panicking.
+/// let _ = std::panic::catch_unwind(|| {
+///     let _stolen = thing.lock_steal();
+///     panic!("poison the mutex");
+/// });
+///
+/// assert!(thing.steal_or_recover(OnPoison::Fail).is_err());
+/// assert_eq!(*thing.steal_or_recover(OnPoison::Recover).unwrap(), 6);
+/// assert!(!thing.is_poisoned());
+/// ```
+pub struct MutexOptionGuard<'a, T: 'a> {
+    lock: MutexGuard<'a, Option<T>>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> MutexOptionGuard<'a, T> {
+    /// Take the value from a locked mutex, or unlock it again if it holds `None`.
+    fn new(mut lock: MutexGuard<'a, Option<T>>) -> Option<MutexOptionGuard<'a, T>> {
+        let value = lock.take()?;
+
+        Some(MutexOptionGuard {
+            lock,
+            value: Some(value),
+        })
+    }
+
+
+    /// Keep the value stolen from the `Mutex` and do not return it. The mutex is left holding
+    /// `None` and unlocked.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for MutexOptionGuard<'a, T> {
+    fn drop(&mut self) {
+        *self.lock = self.value.take();
+    }
+}
+
+
+impl<'a, T> Deref for MutexOptionGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for MutexOptionGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+impl<T> MutexOptionExt for Mutex<Option<T>> {
+    type Inner = T;
+
+    #[track_caller]
+    fn lock_steal<'a>(&'a self) -> MutexOptionGuard<'a, T> {
+        let lock = self.lock().expect("attempted to steal from a poisoned Mutex");
+
+        // The lock is released before panicking on `None`, so the panic doesn't poison the mutex.
+        match MutexOptionGuard::new(lock) {
+            Some(guard) => guard,
+            None => violation::steal_from_none::<T, _>(self),
+        }
+    }
+
+    #[track_caller]
+    fn steal_or_recover<'a>(
+        &'a self,
+        on_poison: OnPoison,
+    ) -> Result<MutexOptionGuard<'a, T>, Poisoned> {
+        match self.try_lock_steal(on_poison)? {
+            Some(guard) => Ok(guard),
+            None => violation::steal_from_none::<T, _>(self),
+        }
+    }
+
+    fn try_lock_steal<'a>(
+        &'a self,
+        on_poison: OnPoison,
+    ) -> Result<Option<MutexOptionGuard<'a, T>>, Poisoned> {
+        let lock = match self.lock() {
+            Ok(lock) => lock,
+            Err(poisoned) => match on_poison {
+                OnPoison::Recover => {
+                    self.clear_poison();
+                    poisoned.into_inner()
+                }
+                OnPoison::Fail => return Err(Poisoned),
+            },
+        };
+
+        Ok(MutexOptionGuard::new(lock))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic;
+
+    fn poisoned(value: i32) -> Mutex<Option<i32>> {
+        let mutex = Mutex::new(Some(value));
+
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _stolen = mutex.lock_steal();
+            panic!();
+        }));
+
+        mutex
+    }
+
+    #[test]
+    fn lock_steal_and_keep() {
+        let thing = Mutex::new(Some(5));
+
+        assert_eq!(thing.lock_steal().into_inner(), 5);
+        assert_eq!(*thing.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn poison_restores_value() {
+        let thing = poisoned(5);
+
+        assert!(thing.is_poisoned());
+        assert_eq!(*thing.lock().unwrap_err().into_inner(), Some(5));
+    }
+
+    #[test]
+    fn recover_from_poison() {
+        let thing = poisoned(5);
+
+        assert_eq!(thing.steal_or_recover(OnPoison::Fail).err(), Some(Poisoned));
+
+        {
+            let mut stolen = thing.steal_or_recover(OnPoison::Recover).unwrap();
+            *stolen += 1;
+        }
+
+        assert_eq!(*thing.lock().unwrap(), Some(6));
+    }
+
+    #[test]
+    fn steal_through_held_lock() {
+        use EmptyOptionExt;
+
+        let thing = Mutex::new(Some(5));
+        let mut lock = thing.lock().unwrap();
+
+        assert_eq!(lock.steal_mut().into_inner(), 5);
+        assert_eq!(*lock, None);
+    }
+
+    #[test]
+    fn steal_from_none_leaves_mutex_unpoisoned() {
+        let thing = Mutex::new(None::<i32>);
+
+        assert!(panic::catch_unwind(|| thing.lock_steal()).is_err());
+        assert!(panic::catch_unwind(|| thing.steal_or_recover(OnPoison::Fail)).is_err());
+        assert!(!thing.is_poisoned());
+
+        assert!(thing.try_lock_steal(OnPoison::Fail).unwrap().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn lock_steal_poisoned() {
+        poisoned(5).lock_steal();
+    }
+}
@@ -0,0 +1,154 @@
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+
+use violation;
+
+
+/// Extension trait providing `steal_pin_mut` on pinned options.
+pub trait PinOptionExt<'a> {
+    type Inner;
+
+    /// Take a pinned value out of an option, providing a guard which returns the value unless
+    /// consumed. Panics on `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_pin_mut(self) -> PinGuardMut<'a, Self::Inner>;
+}
+
+
+/// A value taken from a pinned `Option<T>`. `PinGuardMut<T>` is the pinned counterpart of
+/// `OptionGuardMut`: it dereferences to a `T`, returns the value to the `Option` when dropped, and
+/// can project to a `Pin<&mut T>` with `PinGuardMut::as_pin_mut`, so `Future`s and other `!Unpin`
+/// values can be driven through the guard.
+///
+/// An `OptionGuardMut` moves its value out of the `Option` and back again, so it cannot offer
+/// pinned access. A `PinGuardMut` instead leaves the value exactly where it was pinned, and only
+/// allows it to be moved out (with `DerefMut` or `PinGuardMut::into_inner`) when `T: Unpin`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::PinOptionExt;
+/// use std::future::{self, Future};
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+///
+/// let mut slot = pin!(Some(future::ready(5)));
+///
+/// {
+///     let mut stolen = slot.as_mut().steal_pin_mut();
+///
+///     let mut cx = Context::from_waker(Waker::noop());
+///     assert_eq!(stolen.as_pin_mut().poll(&mut cx), Poll::Ready(5));
+/// }
+///
+/// assert!(slot.is_some());
+/// ```
+pub struct PinGuardMut<'a, T: 'a> {
+    origin: Pin<&'a mut Option<T>>,
+}
+
+
+impl<'a, T> PinGuardMut<'a, T> {
+    /// Get pinned mutable access to the stolen value.
+    pub fn as_pin_mut(&mut self) -> Pin<&mut T> {
+        self.origin.as_mut().as_pin_mut().unwrap()
+    }
+
+
+    /// Drop the stolen value in place, leaving the `Option` empty.
+    pub fn clear(mut self) {
+        self.origin.set(None);
+    }
+}
+
+
+impl<'a, T: Unpin> PinGuardMut<'a, T> {
+    /// Keep the value stolen from the `Option` and do not return it.
+    pub fn into_inner(self) -> T {
+        Pin::into_inner(self.origin).take().unwrap()
+    }
+}
+
+
+impl<'a, T> Deref for PinGuardMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.origin.as_ref().get_ref().as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T: Unpin> DerefMut for PinGuardMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.origin.as_mut().get_mut().as_mut().unwrap()
+    }
+}
+
+
+impl<'a, T> PinOptionExt<'a> for Pin<&'a mut Option<T>> {
+    type Inner = T;
+
+    #[track_caller]
+    fn steal_pin_mut(self) -> PinGuardMut<'a, T> {
+        if self.is_none() {
+            violation::steal_from_none::<T, _>(&*self);
+        }
+
+        PinGuardMut { origin: self }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::marker::PhantomPinned;
+    use std::pin::pin;
+
+    #[test]
+    fn pinned_value_stays_put() {
+        let mut slot = pin!(Some((5, PhantomPinned)));
+        let address = slot.as_ref().get_ref().as_ref().unwrap() as *const _;
+
+        {
+            let mut stolen = slot.as_mut().steal_pin_mut();
+
+            assert_eq!(stolen.0, 5);
+            assert_eq!(&*stolen.as_pin_mut() as *const _, address);
+        }
+
+        assert!(slot.is_some());
+
+        slot.as_mut().steal_pin_mut().clear();
+
+        assert!(slot.is_none());
+    }
+
+    #[test]
+    fn unpin_and_keep() {
+        let mut thing = Some(5);
+
+        {
+            let mut stolen = Pin::new(&mut thing).steal_pin_mut();
+
+            *stolen = 6;
+
+            assert_eq!(stolen.into_inner(), 6);
+        }
+
+        assert_eq!(thing, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pin_from_none() {
+        let mut thing: Option<i32> = None;
+
+        Pin::new(&mut thing).steal_pin_mut();
+    }
+}
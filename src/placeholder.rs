@@ -0,0 +1,188 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+
+/// A type which declares the value that stands in for it while it is stolen: an enum's `Invalid`
+/// variant, an empty collection, a null handle, and so on. `Option<T>` is itself a `Placeholder`,
+/// standing in as `None`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{Placeholder, PlaceholderExt};
+/// #[derive(Debug, PartialEq)]
+/// enum Connection {
+///     Open(u32),
+///     Invalid,
+/// }
+///
+/// impl Placeholder for Connection {
+///     fn placeholder() -> Connection {
+///         Connection::Invalid
+///     }
+/// }
+///
+/// let mut connection = Connection::Open(1);
+///
+/// {
+///     let stolen = connection.steal_with_placeholder();
+///
+///     assert_eq!(*stolen, Connection::Open(1));
+/// }
+///
+/// assert_eq!(connection, Connection::Open(1));
+///
+/// // Keep the connection, leaving the placeholder behind.
+/// assert_eq!(connection.steal_with_placeholder().into_inner(), Connection::Open(1));
+/// assert_eq!(connection, Connection::Invalid);
+/// ```
+///
+/// With the `derive` feature, `#[derive(Placeholder)]` writes the impl. The placeholder is given
+/// by a `#[placeholder(...)]` attribute, or for a struct without one, built from the placeholders
+/// of its fields:
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// # use empty_option::Placeholder;
+/// #[derive(Debug, PartialEq, Placeholder)]
+/// #[placeholder(Connection::Invalid)]
+/// enum Connection {
+///     Open(u32),
+///     Invalid,
+/// }
+///
+/// #[derive(Placeholder)]
+/// struct Session {
+///     connection: Connection,
+///     log: Vec<String>,
+/// }
+///
+/// let session = Session::placeholder();
+///
+/// assert_eq!(session.connection, Connection::Invalid);
+/// assert!(session.log.is_empty());
+/// ```
+pub trait Placeholder {
+    /// The value which stands in for a stolen value.
+    fn placeholder() -> Self;
+}
+
+
+impl<T> Placeholder for Option<T> {
+    fn placeholder() -> Option<T> {
+        None
+    }
+}
+
+
+#[cfg(feature = "alloc")]
+impl<T> Placeholder for Vec<T> {
+    fn placeholder() -> Vec<T> {
+        Vec::new()
+    }
+}
+
+
+#[cfg(feature = "alloc")]
+impl Placeholder for String {
+    fn placeholder() -> String {
+        String::new()
+    }
+}
+
+
+/// Extension trait providing `steal_with_placeholder` on any `Placeholder` type.
+pub trait PlaceholderExt: Placeholder + Sized {
+    /// Take a value, leaving its placeholder in its place, and providing a guard which returns
+    /// the value unless consumed by `PlaceholderGuard::into_inner`.
+    fn steal_with_placeholder<'a>(&'a mut self) -> PlaceholderGuard<'a, Self>;
+}
+
+
+impl<T: Placeholder> PlaceholderExt for T {
+    fn steal_with_placeholder<'a>(&'a mut self) -> PlaceholderGuard<'a, T> {
+        let value = mem::replace(self, T::placeholder());
+
+        PlaceholderGuard {
+            origin: self,
+            value: Some(value),
+        }
+    }
+}
+
+
+/// A value taken from its place by `PlaceholderExt::steal_with_placeholder`, which left the
+/// value's `Placeholder` behind. `PlaceholderGuard<T>` dereferences to a `T`, and the inner `T` can
+/// be moved out with `PlaceholderGuard::into_inner`. When dropped, the `PlaceholderGuard` moves the
+/// value back, replacing the placeholder.
+pub struct PlaceholderGuard<'a, T: 'a> {
+    origin: &'a mut T,
+    value: Option<T>,
+}
+
+
+impl<'a, T> PlaceholderGuard<'a, T> {
+    /// Keep the stolen value, leaving the placeholder in its place.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for PlaceholderGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            *self.origin = value;
+        }
+    }
+}
+
+
+impl<'a, T> Deref for PlaceholderGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for PlaceholderGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn vec_placeholder() {
+        let mut items = vec![1, 2];
+
+        {
+            let mut stolen = items.steal_with_placeholder();
+
+            stolen.push(3);
+
+            assert_eq!(stolen.len(), 3);
+        }
+
+        assert_eq!(items, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn option_placeholder() {
+        let mut thing = Some(5);
+
+        assert_eq!(thing.steal_with_placeholder().into_inner(), Some(5));
+        assert_eq!(thing, None);
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+use poison;
+
+
+struct PoolState<T> {
+    idle: Vec<T>,
+    outstanding: usize,
+    shutdown: bool,
+}
+
+
+/// A blocking pool of values. `Pool::checkout` takes a value out of the pool, providing a
+/// `PoolGuard` which dereferences to the value and returns it to the pool when dropped, unless
+/// it is consumed with `PoolGuard::into_inner`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::Pool;
+/// let pool = Pool::with_capacity(2, || Vec::<u8>::new());
+///
+/// {
+///     let mut a = pool.checkout().unwrap();
+///     let b = pool.checkout().unwrap();
+///
+///     a.push(1);
+///
+///     // Both values are checked out, so there is nothing left to take.
+///     assert!(pool.try_checkout().is_none());
+///
+///     // Keep `b` for ourselves; it never goes back into the pool.
+///     assert!(b.into_inner().is_empty());
+/// }
+///
+/// pool.shutdown();
+///
+/// assert!(pool.checkout().is_none());
+/// assert_eq!(pool.drain(), vec![vec![1]]);
+/// ```
+pub struct Pool<T> {
+    state: Mutex<PoolState<T>>,
+    returned: Condvar,
+}
+
+
+impl<T> Pool<T> {
+    /// Create a pool holding the given values.
+    pub fn new<I>(values: I) -> Pool<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Pool {
+            state: Mutex::new(PoolState {
+                idle: values.into_iter().collect(),
+                outstanding: 0,
+                shutdown: false,
+            }),
+            returned: Condvar::new(),
+        }
+    }
+
+
+    /// Create a pool holding `capacity` values, each produced by calling `init`.
+    pub fn with_capacity<F>(capacity: usize, mut init: F) -> Pool<T>
+    where
+        F: FnMut() -> T,
+    {
+        Pool::new((0..capacity).map(|_| init()))
+    }
+
+
+    fn lock(&self) -> MutexGuard<'_, PoolState<T>> {
+        poison::lock(&self.state)
+    }
+
+
+    /// Take a value out of the pool, blocking until one is available. Returns `None` once the
+    /// pool has been shut down.
+    pub fn checkout<'a>(&'a self) -> Option<PoolGuard<'a, T>> {
+        let mut state = self.lock();
+
+        loop {
+            if state.shutdown {
+                return None;
+            }
+
+            if let Some(value) = state.idle.pop() {
+                state.outstanding += 1;
+                return Some(PoolGuard::new(self, value));
+            }
+
+            state = poison::wait(&self.returned, state);
+        }
+    }
+
+
+    /// Take a value out of the pool if one is immediately available. Returns `None` if the pool
+    /// is empty or has been shut down.
+    pub fn try_checkout<'a>(&'a self) -> Option<PoolGuard<'a, T>> {
+        let mut state = self.lock();
+
+        if state.shutdown {
+            return None;
+        }
+
+        let value = state.idle.pop()?;
+        state.outstanding += 1;
+
+        Some(PoolGuard::new(self, value))
+    }
+
+
+    /// Shut the pool down. Pending and future checkouts return `None`; values which are currently
+    /// checked out are still returned to the pool when their guards are dropped.
+    pub fn shutdown(&self) {
+        self.lock().shutdown = true;
+        self.returned.notify_all();
+    }
+
+
+    /// Shut the pool down, block until every checked-out value has been returned or consumed, and
+    /// then take all of the values out of the pool.
+    pub fn drain(&self) -> Vec<T> {
+        let mut state = self.lock();
+        state.shutdown = true;
+        self.returned.notify_all();
+
+        while state.outstanding > 0 {
+            state = poison::wait(&self.returned, state);
+        }
+
+        state.idle.drain(..).collect()
+    }
+
+
+    /// The number of values currently sitting in the pool, not counting those checked out.
+    pub fn idle(&self) -> usize {
+        self.lock().idle.len()
+    }
+
+
+    fn check_in(&self, value: Option<T>) {
+        let mut state = self.lock();
+
+        state.outstanding -= 1;
+
+        if let Some(value) = value {
+            state.idle.push(value);
+        }
+
+        drop(state);
+
+        // Wake everyone: both `checkout` and `drain` wait on the same condition variable.
+        self.returned.notify_all();
+    }
+}
+
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.lock();
+
+        f.debug_struct("Pool")
+            .field("idle", &state.idle.len())
+            .field("outstanding", &state.outstanding)
+            .field("shutdown", &state.shutdown)
+            .finish()
+    }
+}
+
+
+/// A value checked out of a `Pool`. `PoolGuard<T>` dereferences to a `T`, and the inner `T` can be
+/// moved out with `PoolGuard::into_inner`. When dropped, the `PoolGuard` returns the value to
+/// the pool it came from.
+pub struct PoolGuard<'a, T: 'a> {
+    pool: &'a Pool<T>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> PoolGuard<'a, T> {
+    fn new(pool: &'a Pool<T>, value: T) -> PoolGuard<'a, T> {
+        PoolGuard {
+            pool,
+            value: Some(value),
+        }
+    }
+
+
+    /// Keep the value checked out of the `Pool` and do not return it.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for PoolGuard<'a, T> {
+    fn drop(&mut self) {
+        self.pool.check_in(self.value.take());
+    }
+}
+
+
+impl<'a, T> Deref for PoolGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for PoolGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn checkout_and_return() {
+        let pool = Pool::new(vec![5]);
+
+        {
+            let mut five = pool.checkout().unwrap();
+
+            assert_eq!(*five, 5);
+            assert!(pool.try_checkout().is_none());
+
+            *five = 6;
+        }
+
+        assert_eq!(*pool.checkout().unwrap(), 6);
+    }
+
+    #[test]
+    fn checkout_blocks_until_returned() {
+        let pool = Arc::new(Pool::with_capacity(1, || 0));
+        let guard = pool.checkout().unwrap();
+
+        let waiter = {
+            let pool = pool.clone();
+            thread::spawn(move || *pool.checkout().unwrap())
+        };
+
+        drop(guard);
+
+        assert_eq!(waiter.join().unwrap(), 0);
+    }
+
+    #[test]
+    fn drain_waits_for_outstanding() {
+        let pool = Arc::new(Pool::new(vec![1, 2]));
+        let guard = pool.checkout().unwrap();
+
+        let drainer = {
+            let pool = pool.clone();
+            thread::spawn(move || pool.drain())
+        };
+
+        drop(guard);
+
+        let mut drained = drainer.join().unwrap();
+        drained.sort();
+
+        assert_eq!(drained, vec![1, 2]);
+        assert!(pool.checkout().is_none());
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::mem;
+use std::panic::Location;
+
+use violation;
+
+
+/// Take a value out from behind a plain `&mut T`, leaving a caller-supplied sentinel in its place,
+/// and providing a guard which panics if the value is not restored. This works for any `T` - no
+/// `Option`, `Default` or `Placeholder` is needed, only some value which can stand in while the
+/// real one is away.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::replace_guarded;
+/// #[derive(Debug, PartialEq)]
+/// enum Link {
+///     Down { attempts: u32 },
+///     Up(String),
+/// }
+///
+/// let mut link = Link::Down { attempts: 2 };
+///
+/// {
+///     // Anyone looking at `link` meanwhile sees a link which has given up.
+///     let (guard, previous) = replace_guarded(&mut link, Link::Down { attempts: u32::MAX });
+///
+///     let next = match previous {
+///         Link::Down { attempts } if attempts < 3 => Link::Up(String::from("eth0")),
+///         other => other,
+///     };
+///
+///     assert_eq!(guard.restore(next), Link::Down { attempts: u32::MAX });
+/// }
+///
+/// assert_eq!(link, Link::Up(String::from("eth0")));
+/// ```
+#[track_caller]
+pub fn replace_guarded<T>(value: &mut T, sentinel: T) -> (ReplaceGuard<'_, T>, T) {
+    let taken = mem::replace(value, sentinel);
+
+    let guard = ReplaceGuard {
+        origin: value,
+        location: Location::caller(),
+    };
+
+    (guard, taken)
+}
+
+
+/// A reference whose value was swapped for a sentinel by `replace_guarded`. Code which looks at
+/// the reference while the value is away - after a panic unwinds past the guard, say - finds the
+/// sentinel. `ReplaceGuard::restore` swaps the real value back in and hands the sentinel back.
+/// Dropping the guard without a restore panics with a `GuardLeaked`, leaving the sentinel in
+/// place.
+pub struct ReplaceGuard<'a, T: 'a> {
+    origin: &'a mut T,
+    location: &'static Location<'static>,
+}
+
+
+impl<'a, T> ReplaceGuard<'a, T> {
+    /// Restore a value, returning the sentinel it replaces.
+    pub fn restore(self, obj: T) -> T {
+        let sentinel = mem::replace(self.origin, obj);
+
+        mem::forget(self);
+        sentinel
+    }
+
+
+    /// The sentinel standing in for the taken value.
+    pub fn sentinel(&self) -> &T {
+        self.origin
+    }
+}
+
+
+impl<'a, T> Drop for ReplaceGuard<'a, T> {
+    fn drop(&mut self) {
+        violation::raise(violation::leak_of::<T, _>(self.origin, self.location, None));
+    }
+}
+
+
+impl<'a, T: fmt::Debug> fmt::Debug for ReplaceGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReplaceGuard")
+            .field("sentinel", self.sentinel())
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn restore_returns_sentinel() {
+        let mut state = String::from("real");
+
+        let (guard, real) = replace_guarded(&mut state, String::from("sentinel"));
+
+        assert_eq!(guard.sentinel(), "sentinel");
+        assert_eq!(guard.restore(real + "!"), "sentinel");
+        assert_eq!(state, "real!");
+    }
+
+    #[test]
+    fn leak_leaves_sentinel() {
+        let mut state = 1;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (_, _) = replace_guarded(&mut state, -1);
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(state, -1);
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+use std::mem;
+use std::panic::Location;
+
+use {violation, Placeholder};
+
+
+/// A stolen payload of type `V`, along with the guard for the result it was stolen from.
+type Stolen<'a, T, E, V> = Option<(ResultGuard<'a, T, E>, V)>;
+
+
+/// Extension trait providing `steal_ok` and `steal_err` on `Result`s.
+///
+/// A `Result` has no empty state to leave behind, so while one variant's payload is stolen the
+/// result holds the other variant's `Placeholder`. The `ResultGuard` which comes with the payload
+/// panics if it is dropped without a restore, and can restore either variant - so a state machine
+/// held in a `Result` can take its state out, transform it, and put it back as the other variant.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::ResultStealExt;
+/// let mut session: Result<u32, Option<String>> = Ok(3);
+///
+/// {
+///     let (guard, retries) = session.steal_ok().unwrap();
+///
+///     // While the value is stolen, the result holds the placeholder `Err(None)`.
+///     if retries == 3 {
+///         guard.restore_err(Some(String::from("out of retries")));
+///     } else {
+///         guard.restore_ok(retries + 1);
+///     }
+/// }
+///
+/// assert_eq!(session, Err(Some(String::from("out of retries"))));
+/// assert!(session.steal_ok().is_none());
+/// ```
+pub trait ResultStealExt {
+    type Ok;
+    type Err;
+
+    /// Take the `Ok` payload, leaving an `Err` placeholder, and providing a guard which panics if
+    /// the result is not restored. Returns `None`, leaving the result untouched, if it is an
+    /// `Err`.
+    fn steal_ok<'a>(&'a mut self) -> Stolen<'a, Self::Ok, Self::Err, Self::Ok>
+    where
+        Self::Err: Placeholder;
+
+    /// Take the `Err` payload, leaving an `Ok` placeholder, and providing a guard which panics if
+    /// the result is not restored. Returns `None`, leaving the result untouched, if it is an
+    /// `Ok`.
+    fn steal_err<'a>(&'a mut self) -> Stolen<'a, Self::Ok, Self::Err, Self::Err>
+    where
+        Self::Ok: Placeholder;
+}
+
+
+impl<T, E> ResultStealExt for Result<T, E> {
+    type Ok = T;
+    type Err = E;
+
+    #[track_caller]
+    fn steal_ok<'a>(&'a mut self) -> Option<(ResultGuard<'a, T, E>, T)>
+    where
+        E: Placeholder,
+    {
+        if self.is_err() {
+            return None;
+        }
+
+        match mem::replace(self, Err(E::placeholder())) {
+            Ok(value) => Some((ResultGuard::new(self), value)),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    #[track_caller]
+    fn steal_err<'a>(&'a mut self) -> Option<(ResultGuard<'a, T, E>, E)>
+    where
+        T: Placeholder,
+    {
+        if self.is_ok() {
+            return None;
+        }
+
+        match mem::replace(self, Ok(T::placeholder())) {
+            Err(error) => Some((ResultGuard::new(self), error)),
+            Ok(_) => unreachable!(),
+        }
+    }
+}
+
+
+/// A result which has had a payload taken by `ResultStealExt::steal_ok` or
+/// `ResultStealExt::steal_err`, and which holds a placeholder until it is restored. Dropping the
+/// guard without restoring the result panics with a `GuardLeaked`, leaving the placeholder in
+/// place.
+pub struct ResultGuard<'a, T: 'a, E: 'a> {
+    result: &'a mut Result<T, E>,
+    location: &'static Location<'static>,
+}
+
+
+impl<'a, T, E> ResultGuard<'a, T, E> {
+    #[track_caller]
+    fn new(result: &'a mut Result<T, E>) -> ResultGuard<'a, T, E> {
+        ResultGuard {
+            result,
+            location: Location::caller(),
+        }
+    }
+
+
+    /// Restore the result, as either variant.
+    pub fn restore(self, result: Result<T, E>) {
+        *self.result = result;
+        mem::forget(self);
+    }
+
+
+    /// Restore the result as an `Ok`.
+    pub fn restore_ok(self, value: T) {
+        self.restore(Ok(value));
+    }
+
+
+    /// Restore the result as an `Err`.
+    pub fn restore_err(self, error: E) {
+        self.restore(Err(error));
+    }
+}
+
+
+impl<'a, T, E> Drop for ResultGuard<'a, T, E> {
+    fn drop(&mut self) {
+        violation::raise(violation::leak_of::<Result<T, E>, _>(
+            self.result,
+            self.location,
+            None,
+        ));
+    }
+}
+
+
+impl<'a, T, E> fmt::Debug for ResultGuard<'a, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The guard holds only a placeholder, so neither `T` nor `E` need be `Debug`.
+        f.debug_struct("ResultGuard")
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steal_err_restore_ok() {
+        let mut state: Result<Option<i32>, &str> = Err("pending");
+
+        let (guard, pending) = state.steal_err().unwrap();
+
+        assert_eq!(pending, "pending");
+
+        guard.restore_ok(Some(1));
+
+        assert_eq!(state, Ok(Some(1)));
+        assert!(state.steal_err().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn leaked_guard_leaves_placeholder() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        use GuardLeaked;
+
+        let mut state: Result<i32, Option<&str>> = Ok(1);
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (_, _) = state.steal_ok().unwrap();
+        }))
+        .unwrap_err();
+
+        assert!(payload.downcast_ref::<GuardLeaked>().unwrap().is_slot(&state));
+        assert_eq!(state, Err(None));
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use {SharedSlot, SharedSlotGuard};
+
+
+/// A fixed ring of `SharedSlot`s, for distributing work among threads without a full channel.
+///
+/// `SlotRing::steal_any` scans the ring round-robin, starting just after the slot it last stole
+/// from, and steals from the first occupied slot it finds. Producers can refill vacant slots
+/// with `SlotRing::put_any`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::SlotRing;
+/// let ring = SlotRing::new(vec![1, 2, 3]);
+///
+/// let a = ring.steal_any().unwrap();
+/// let b = ring.steal_any().unwrap();
+/// let c = ring.steal_any().unwrap();
+///
+/// assert_eq!((*a, *b, *c), (1, 2, 3));
+/// assert!(ring.steal_any().is_none());
+///
+/// // Returning a value makes its slot available again.
+/// drop(b);
+/// assert_eq!(*ring.steal_any().unwrap(), 2);
+/// ```
+pub struct SlotRing<T> {
+    slots: Vec<SharedSlot<T>>,
+    cursor: AtomicUsize,
+}
+
+
+impl<T> SlotRing<T> {
+    /// Create a ring with one occupied slot for each value.
+    pub fn new<I>(values: I) -> SlotRing<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        SlotRing::from_slots(values.into_iter().map(SharedSlot::new).collect())
+    }
+
+
+    /// Create a ring of `len` vacant slots.
+    pub fn empty(len: usize) -> SlotRing<T> {
+        SlotRing::from_slots((0..len).map(|_| SharedSlot::empty()).collect())
+    }
+
+
+    fn from_slots(slots: Vec<SharedSlot<T>>) -> SlotRing<T> {
+        SlotRing {
+            slots,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+
+    /// Steal from the next occupied slot in round-robin order, if any slot is occupied.
+    pub fn steal_any<'a>(&'a self) -> Option<SharedSlotGuard<'a, T>> {
+        let start = self.cursor.load(Ordering::Relaxed);
+
+        for offset in 0..self.slots.len() {
+            let index = (start + offset) % self.slots.len();
+
+            if let Some(guard) = self.slots[index].try_steal() {
+                self.cursor.store(index + 1, Ordering::Relaxed);
+                return Some(guard);
+            }
+        }
+
+        None
+    }
+
+
+    /// Put a value into the first vacant slot, scanning from the start of the ring. If no slot is
+    /// vacant, the value is handed back.
+    pub fn put_any(&self, mut value: T) -> Result<(), T> {
+        for slot in &self.slots {
+            match slot.put(value) {
+                Ok(()) => return Ok(()),
+                Err(rejected) => value = rejected,
+            }
+        }
+
+        Err(value)
+    }
+
+
+    /// Get the slot at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<&SharedSlot<T>> {
+        self.slots.get(index)
+    }
+
+
+    /// The number of slots in the ring.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+
+    /// Returns `true` if the ring has no slots at all.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+
+impl<T> fmt::Debug for SlotRing<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SlotRing")
+            .field("slots", &self.slots)
+            .field("cursor", &self.cursor.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn round_robin() {
+        let ring = SlotRing::new(vec![0, 1, 2]);
+
+        let seen: Vec<_> = (0..6).map(|_| *ring.steal_any().unwrap()).collect();
+
+        assert_eq!(seen, vec![0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn put_any_fills_vacancies() {
+        let ring = SlotRing::empty(2);
+
+        assert!(ring.steal_any().is_none());
+        assert_eq!(ring.put_any(1), Ok(()));
+        assert_eq!(ring.put_any(2), Ok(()));
+        assert_eq!(ring.put_any(3), Err(3));
+
+        assert_eq!(ring.steal_any().unwrap().into_inner(), 1);
+        assert!(!ring.get(0).unwrap().is_occupied());
+    }
+
+    #[test]
+    fn workers_share_ring() {
+        let ring = Arc::new(SlotRing::new(vec![0; 4]));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let ring = ring.clone();
+                thread::spawn(move || {
+                    let mut done = 0;
+
+                    while done < 100 {
+                        if let Some(mut counter) = ring.steal_any() {
+                            *counter += 1;
+                            done += 1;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let total: i32 = (0..4).map(|_| ring.steal_any().unwrap().into_inner()).sum();
+        assert_eq!(total, 400);
+    }
+}
@@ -0,0 +1,321 @@
+use std::panic;
+use std::thread;
+
+use {violation, EmptyOptionExt, StealError};
+
+
+/// Steal the value from every option in `slots`, process each value on its own scoped thread
+/// with `f`, and restore the results once every thread has been joined.
+///
+/// All of the slots are checked before anything is stolen, so a `None` anywhere in `slots` panics
+/// with `StealFromNone` while leaving every slot untouched. If `f` panics on any thread, the
+/// remaining results are still restored, the slot whose worker panicked is left `None`, and the
+/// worker's panic is resumed on the calling thread.
+///
+/// Guards are `Send` whenever the values they guard are, so the same pattern can be written by
+/// hand with `std::thread::scope` when the workers need to do more than map a value.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::steal_scoped_threads;
+/// let mut slots = vec![Some(1), Some(2), Some(3)];
+///
+/// steal_scoped_threads(&mut slots, |value| value * 10);
+///
+/// assert_eq!(slots, vec![Some(10), Some(20), Some(30)]);
+/// ```
+#[cfg_attr(
+    feature = "strict",
+    deprecated(note = "panics on `None`, which the `strict` feature forbids; use `try_steal_scoped_threads`")
+)]
+#[track_caller]
+pub fn steal_scoped_threads<T, F>(slots: &mut [Option<T>], f: F)
+where
+    T: Send,
+    F: Fn(T) -> T + Sync,
+{
+    if let Some(empty) = slots.iter().find(|slot| slot.is_none()) {
+        violation::steal_from_none::<T, _>(empty);
+    }
+
+    scoped_threads(slots, f);
+}
+
+
+/// Like `steal_scoped_threads`, but returning a `StealError`, with every slot left untouched, if
+/// any of `slots` is `None` instead of panicking.
+pub fn try_steal_scoped_threads<T, F>(slots: &mut [Option<T>], f: F) -> Result<(), StealError>
+where
+    T: Send,
+    F: Fn(T) -> T + Sync,
+{
+    if slots.iter().any(Option::is_none) {
+        return Err(StealError);
+    }
+
+    scoped_threads(slots, f);
+
+    Ok(())
+}
+
+
+/// The body of `steal_scoped_threads`, for slots already known to be full.
+fn scoped_threads<T, F>(slots: &mut [Option<T>], f: F)
+where
+    T: Send,
+    F: Fn(T) -> T + Sync,
+{
+    let (guards, values): (Vec<_>, Vec<_>) = slots.iter_mut().map(|slot| slot.steal()).unzip();
+
+    let f = &f;
+    let results: Vec<_> = thread::scope(|scope| {
+        let workers: Vec<_> = values
+            .into_iter()
+            .map(|value| scope.spawn(move || f(value)))
+            .collect();
+
+        workers.into_iter().map(|worker| worker.join()).collect()
+    });
+
+    let mut first_panic = None;
+
+    for (guard, result) in guards.into_iter().zip(results) {
+        match result {
+            Ok(value) => guard.restore(value),
+            Err(payload) => {
+                guard.into_slot();
+                first_panic.get_or_insert(payload);
+            }
+        }
+    }
+
+    if let Some(payload) = first_panic {
+        panic::resume_unwind(payload);
+    }
+}
+
+
+/// Steal the values from two options and process them in parallel, like the two branches of a
+/// fork-join `join`: `fa` runs on a scoped thread while `fb` runs on the calling thread. Each
+/// branch hands back the value to restore alongside its result.
+///
+/// Both options are checked before anything is stolen, so a `None` in either panics with
+/// `StealFromNone` without touching the other. If a branch panics, the other branch's value is
+/// still restored, the panicking branch's option is left `None`, and the panic is resumed.
+///
+/// The branches only need to be `Send`, not `'static`, and guards are `Send` whenever their
+/// values are, so the same shape works unchanged inside `rayon::join` or `rayon::scope`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::join_steal;
+/// let mut left = Some(vec![3, 1, 2]);
+/// let mut right = Some(String::from("hello"));
+///
+/// let (len, upper) = join_steal(
+///     &mut left,
+///     &mut right,
+///     |mut v| {
+///         v.sort();
+///         let len = v.len();
+///         (v, len)
+///     },
+///     |s| {
+///         let upper = s.to_uppercase();
+///         (s, upper)
+///     },
+/// );
+///
+/// assert_eq!(len, 3);
+/// assert_eq!(upper, "HELLO");
+/// assert_eq!(left, Some(vec![1, 2, 3]));
+/// assert_eq!(right, Some(String::from("hello")));
+/// ```
+#[cfg_attr(
+    feature = "strict",
+    deprecated(note = "panics on `None`, which the `strict` feature forbids; use `try_join_steal`")
+)]
+#[track_caller]
+pub fn join_steal<A, B, RA, RB, FA, FB>(
+    a: &mut Option<A>,
+    b: &mut Option<B>,
+    fa: FA,
+    fb: FB,
+) -> (RA, RB)
+where
+    A: Send,
+    RA: Send,
+    FA: FnOnce(A) -> (A, RA) + Send,
+    FB: FnOnce(B) -> (B, RB),
+{
+    if a.is_none() {
+        violation::steal_from_none::<A, _>(a);
+    }
+
+    if b.is_none() {
+        violation::steal_from_none::<B, _>(b);
+    }
+
+    join(a, b, fa, fb)
+}
+
+
+/// Like `join_steal`, but returning a `StealError`, with both options left untouched, if either
+/// is `None` instead of panicking.
+pub fn try_join_steal<A, B, RA, RB, FA, FB>(
+    a: &mut Option<A>,
+    b: &mut Option<B>,
+    fa: FA,
+    fb: FB,
+) -> Result<(RA, RB), StealError>
+where
+    A: Send,
+    RA: Send,
+    FA: FnOnce(A) -> (A, RA) + Send,
+    FB: FnOnce(B) -> (B, RB),
+{
+    if a.is_none() || b.is_none() {
+        return Err(StealError);
+    }
+
+    Ok(join(a, b, fa, fb))
+}
+
+
+/// The body of `join_steal`, for options already known to be full.
+fn join<A, B, RA, RB, FA, FB>(a: &mut Option<A>, b: &mut Option<B>, fa: FA, fb: FB) -> (RA, RB)
+where
+    A: Send,
+    RA: Send,
+    FA: FnOnce(A) -> (A, RA) + Send,
+    FB: FnOnce(B) -> (B, RB),
+{
+    let (guard_a, value_a) = a.steal();
+    let (guard_b, value_b) = b.steal();
+
+    let (result_a, result_b) = thread::scope(|scope| {
+        let branch_a = scope.spawn(move || fa(value_a));
+        let result_b = panic::catch_unwind(panic::AssertUnwindSafe(move || fb(value_b)));
+
+        (branch_a.join(), result_b)
+    });
+
+    let result_a = match result_a {
+        Ok((value, result)) => {
+            guard_a.restore(value);
+            Ok(result)
+        }
+        Err(payload) => {
+            guard_a.into_slot();
+            Err(payload)
+        }
+    };
+
+    let result_b = match result_b {
+        Ok((value, result)) => {
+            guard_b.restore(value);
+            Ok(result)
+        }
+        Err(payload) => {
+            guard_b.into_slot();
+            Err(payload)
+        }
+    };
+
+    match (result_a, result_b) {
+        (Ok(ra), Ok(rb)) => (ra, rb),
+        (Err(payload), _) | (_, Err(payload)) => panic::resume_unwind(payload),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use {OptionGuard, OptionGuardMut, PoolGuard};
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn guards_are_send() {
+        assert_send::<OptionGuard<'static, i32>>();
+        assert_send::<OptionGuardMut<'static, i32>>();
+        assert_send::<PoolGuard<'static, i32>>();
+    }
+
+    #[test]
+    fn restore_from_scoped_thread() {
+        let mut thing = Some(5);
+
+        {
+            let (guard, five) = thing.steal();
+
+            thread::scope(|scope| {
+                scope.spawn(move || guard.restore(five + 1));
+            });
+        }
+
+        assert_eq!(thing, Some(6));
+    }
+
+    #[test]
+    fn worker_panic_is_resumed() {
+        let mut slots = vec![Some(1), Some(2)];
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            steal_scoped_threads(&mut slots, |value| {
+                assert!(value != 2);
+                value
+            });
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(slots, vec![Some(1), None]);
+    }
+
+    #[test]
+    fn join_branch_panic_restores_other() {
+        let mut a = Some(1);
+        let mut b = Some(2);
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            join_steal(&mut a, &mut b, |x| (x + 10, ()), |_| -> (i32, ()) { panic!() });
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(a, Some(11));
+        assert_eq!(b, None);
+    }
+
+    #[test]
+    fn try_variants_leave_slots_untouched() {
+        let mut slots = vec![Some(1), None];
+
+        assert_eq!(try_steal_scoped_threads(&mut slots, |value| value + 1), Err(StealError));
+        assert_eq!(slots, vec![Some(1), None]);
+
+        let (mut a, mut b) = (Some(1), None::<i32>);
+
+        assert_eq!(
+            try_join_steal(&mut a, &mut b, |x| (x + 1, ()), |y| (y, ())),
+            Err(StealError)
+        );
+        assert_eq!(a, Some(1));
+
+        slots[1] = Some(2);
+
+        assert_eq!(try_steal_scoped_threads(&mut slots, |value| value + 1), Ok(()));
+        assert_eq!(slots, vec![Some(2), Some(3)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_slot_panics_untouched() {
+        let mut slots = vec![Some(1), None];
+
+        steal_scoped_threads(&mut slots, |value| value);
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+use std::mem;
+use std::panic::Location;
+
+use violation;
+
+
+/// An option whose value was swapped for a placeholder by `EmptyOptionExt::steal_replace`. Code
+/// which looks at the option while the value is stolen - through the guard, or after a panic
+/// unwinds past it - finds the placeholder rather than `None`. `SentinelGuard::restore` swaps the
+/// real value back in and hands the placeholder back. Like an `OptionGuard`, the guard panics if
+/// it is dropped without a restore, leaving the placeholder in place.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// let mut status = Some(String::from("ready"));
+///
+/// let (guard, ready) = status.steal_replace(String::from("busy"));
+///
+/// assert_eq!(guard.placeholder(), "busy");
+///
+/// let busy = guard.restore(ready);
+///
+/// assert_eq!((status.as_deref(), &busy[..]), (Some("ready"), "busy"));
+/// ```
+pub struct SentinelGuard<'a, T: 'a> {
+    opt: &'a mut Option<T>,
+    location: &'static Location<'static>,
+}
+
+
+impl<'a, T> SentinelGuard<'a, T> {
+    #[track_caller]
+    pub(crate) fn new(opt: &'a mut Option<T>) -> SentinelGuard<'a, T> {
+        SentinelGuard {
+            opt,
+            location: Location::caller(),
+        }
+    }
+
+
+    /// Restore a stolen value to the `Option`, returning the placeholder it replaces.
+    pub fn restore(self, obj: T) -> T {
+        let placeholder = self.opt.replace(obj);
+
+        mem::forget(self);
+        placeholder.unwrap()
+    }
+
+
+    /// The placeholder standing in for the stolen value.
+    pub fn placeholder(&self) -> &T {
+        self.opt.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for SentinelGuard<'a, T> {
+    fn drop(&mut self) {
+        violation::raise(violation::leak_of::<T, _>(self.opt, self.location, None));
+    }
+}
+
+
+impl<'a, T: fmt::Debug> fmt::Debug for SentinelGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SentinelGuard")
+            .field("placeholder", self.placeholder())
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use EmptyOptionExt;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn leak_leaves_placeholder() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        use GuardLeaked;
+
+        let mut thing = Some(5);
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (guard, _) = thing.steal_replace(0);
+
+            assert_eq!(*guard.placeholder(), 0);
+        }))
+        .unwrap_err();
+
+        assert!(payload.downcast_ref::<GuardLeaked>().unwrap().is_slot(&thing));
+        assert_eq!(thing, Some(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn replace_from_none() {
+        let mut thing: Option<i32> = None;
+
+        thing.steal_replace(0);
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+use poison;
+
+
+struct State<T> {
+    value: Option<T>,
+    stolen: bool,
+}
+
+
+/// A slot shared between threads, whose value can be stolen by one thread at a time.
+///
+/// Unlike a `Mutex<Option<T>>`, the slot is not kept locked while its value is stolen: the
+/// `SharedSlotGuard` owns the value, and returns it to the slot (waking any threads waiting in
+/// `SharedSlot::steal_wait`) when dropped. A guard consumed with `SharedSlotGuard::into_inner`
+/// leaves the slot vacant until a producer `put`s a new value in, which makes a `SharedSlot` a
+/// tiny one-item channel. `AsyncSlot` is the asynchronous counterpart.
+///
+/// Both constructors are `const`, so a `SharedSlot` can be a `static` singleton without any lazy
+/// initialization.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::SharedSlot;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let slot = Arc::new(SharedSlot::<i32>::empty());
+///
+/// let consumer = {
+///     let slot = slot.clone();
+///     thread::spawn(move || slot.steal_wait().into_inner())
+/// };
+///
+/// slot.put(5).unwrap();
+///
+/// assert_eq!(consumer.join().unwrap(), 5);
+/// ```
+pub struct SharedSlot<T> {
+    state: Mutex<State<T>>,
+    filled: Condvar,
+}
+
+
+impl<T> SharedSlot<T> {
+    /// Create a new slot holding a value.
+    pub const fn new(value: T) -> SharedSlot<T> {
+        SharedSlot::from_option(Some(value))
+    }
+
+
+    /// Create a new, vacant slot.
+    pub const fn empty() -> SharedSlot<T> {
+        SharedSlot::from_option(None)
+    }
+
+
+    const fn from_option(value: Option<T>) -> SharedSlot<T> {
+        SharedSlot {
+            state: Mutex::new(State {
+                value,
+                stolen: false,
+            }),
+            filled: Condvar::new(),
+        }
+    }
+
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        poison::lock(&self.state)
+    }
+
+
+    /// Take the value out of the slot if it holds one right now, providing a guard which returns
+    /// the value when dropped unless consumed by `SharedSlotGuard::into_inner`.
+    pub fn try_steal<'a>(&'a self) -> Option<SharedSlotGuard<'a, T>> {
+        let mut state = self.lock();
+
+        let value = state.value.take()?;
+        state.stolen = true;
+
+        Some(SharedSlotGuard::new(self, value))
+    }
+
+
+    /// Block until the slot holds a value, and then take it, providing a guard which returns the
+    /// value when dropped unless consumed by `SharedSlotGuard::into_inner`.
+    pub fn steal_wait<'a>(&'a self) -> SharedSlotGuard<'a, T> {
+        let mut state = self.lock();
+
+        loop {
+            if let Some(value) = state.value.take() {
+                state.stolen = true;
+                return SharedSlotGuard::new(self, value);
+            }
+
+            state = poison::wait(&self.filled, state);
+        }
+    }
+
+
+    /// Wait until the slot holds a value, steal it, run `f` on it, and return it to the slot,
+    /// returning `f`'s result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::SharedSlot;
+    /// let slot = SharedSlot::new(vec![1, 2]);
+    ///
+    /// assert_eq!(slot.with(|items| { items.push(3); items.len() }), 3);
+    /// assert_eq!(slot.into_inner(), Some(vec![1, 2, 3]));
+    /// ```
+    pub fn with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        f(&mut self.steal_wait())
+    }
+
+
+    /// Like `SharedSlot::with`, but returning `None` instead of waiting if the slot does not hold a
+    /// value right now.
+    pub fn try_with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        self.try_steal().map(|mut guard| f(&mut guard))
+    }
+
+
+    /// Fill a vacant slot with a value, waking any threads waiting to steal it. If the slot already
+    /// holds a value, or its value is currently stolen, the value is handed back.
+    pub fn put(&self, value: T) -> Result<(), T> {
+        let mut state = self.lock();
+
+        if state.stolen || state.value.is_some() {
+            return Err(value);
+        }
+
+        state.value = Some(value);
+        drop(state);
+
+        self.filled.notify_one();
+
+        Ok(())
+    }
+
+
+    /// Returns `true` if the slot holds a value which is not currently stolen.
+    pub fn is_occupied(&self) -> bool {
+        self.lock().value.is_some()
+    }
+
+
+    /// Consume the slot, returning the value in it, if any.
+    pub fn into_inner(self) -> Option<T> {
+        poison::into_inner(self.state).value
+    }
+
+
+    fn check_in(&self, value: Option<T>) {
+        let mut state = self.lock();
+
+        state.stolen = false;
+
+        if value.is_some() {
+            state.value = value;
+            drop(state);
+
+            self.filled.notify_one();
+        }
+    }
+}
+
+
+impl<T> fmt::Debug for SharedSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.lock();
+
+        f.debug_struct("SharedSlot")
+            .field("occupied", &state.value.is_some())
+            .field("stolen", &state.stolen)
+            .finish()
+    }
+}
+
+
+/// A value stolen from a `SharedSlot`. `SharedSlotGuard<T>` dereferences to a `T`, and the inner
+/// `T` can be moved out with `SharedSlotGuard::into_inner`, leaving the slot vacant. When dropped,
+/// the `SharedSlotGuard` returns the value to the slot and wakes a thread waiting to steal it.
+pub struct SharedSlotGuard<'a, T: 'a> {
+    slot: &'a SharedSlot<T>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> SharedSlotGuard<'a, T> {
+    fn new(slot: &'a SharedSlot<T>, value: T) -> SharedSlotGuard<'a, T> {
+        SharedSlotGuard {
+            slot,
+            value: Some(value),
+        }
+    }
+
+
+    /// Keep the value stolen from the slot and do not return it. The slot is left vacant, ready to
+    /// be refilled with `SharedSlot::put`.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for SharedSlotGuard<'a, T> {
+    fn drop(&mut self) {
+        self.slot.check_in(self.value.take());
+    }
+}
+
+
+impl<'a, T> Deref for SharedSlotGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for SharedSlotGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn steal_wait_for_restore() {
+        let slot = Arc::new(SharedSlot::new(0));
+        let guard = slot.try_steal().unwrap();
+
+        assert!(slot.try_steal().is_none());
+        assert_eq!(slot.put(1), Err(1));
+
+        let waiter = {
+            let slot = slot.clone();
+            thread::spawn(move || *slot.steal_wait() += 1)
+        };
+
+        drop(guard);
+        waiter.join().unwrap();
+
+        assert_eq!(*slot.try_steal().unwrap(), 1);
+    }
+
+    #[test]
+    fn static_slot() {
+        static SLOT: SharedSlot<i32> = SharedSlot::empty();
+
+        SLOT.put(5).unwrap();
+        *SLOT.steal_wait() += 1;
+
+        assert_eq!(SLOT.try_steal().unwrap().into_inner(), 6);
+    }
+
+    #[test]
+    fn restored_while_unwinding() {
+        use std::panic;
+
+        let slot = SharedSlot::new(0);
+
+        let result = panic::catch_unwind(|| {
+            let mut stolen = slot.try_steal().unwrap();
+            *stolen += 1;
+            panic!("oops");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(slot.try_steal().unwrap().into_inner(), 1);
+    }
+
+    #[test]
+    fn try_with_vacant() {
+        let slot = SharedSlot::<i32>::empty();
+
+        assert_eq!(slot.try_with(|value| *value), None);
+        assert_eq!(slot.put(1), Ok(()));
+        assert_eq!(slot.try_with(|value| *value), Some(1));
+    }
+
+    #[test]
+    fn rendezvous() {
+        let slot = Arc::new(SharedSlot::<i32>::empty());
+
+        let consumer = {
+            let slot = slot.clone();
+            thread::spawn(move || (0..3).map(|_| slot.steal_wait().into_inner()).sum::<i32>())
+        };
+
+        let mut sent = 0;
+
+        while sent < 3 {
+            if slot.put(sent + 1).is_ok() {
+                sent += 1;
+            }
+
+            thread::yield_now();
+        }
+
+        assert_eq!(consumer.join().unwrap(), 6);
+        assert!(!slot.is_occupied());
+    }
+}
@@ -0,0 +1,82 @@
+use {EmptyOptionExt, OptionGuard, OptionGuardMut};
+
+
+/// Extension trait providing `steal_at` and `steal_mut_at` on slices of options - and so, through
+/// method-call autoderef, on `Vec<Option<T>>`s and arrays of options too.
+///
+/// The guards borrow the element at the index they were made for, so a value always goes back to
+/// the position it came from.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::SliceOptionExt;
+/// let mut positions = vec![Some((0, 0)), None, Some((5, 5))];
+///
+/// {
+///     let (guard, (x, y)) = positions.steal_at(2);
+///
+///     guard.restore((x + 1, y));
+/// }
+///
+/// positions.steal_mut_at(0).0 += 2;
+///
+/// assert_eq!(positions, [Some((2, 0)), None, Some((6, 5))]);
+/// ```
+pub trait SliceOptionExt {
+    type Inner;
+
+    /// Take the value at `index`, providing a guard which panics if the value is not returned.
+    /// Panics if `index` is out of bounds, or if the option there is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_at<'a>(&'a mut self, index: usize) -> (OptionGuard<'a, Self::Inner>, Self::Inner);
+
+    /// Take the value at `index`, providing a guard which returns the value when dropped. Panics
+    /// if `index` is out of bounds, or if the option there is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on `None`, which the `strict` feature forbids")
+    )]
+    fn steal_mut_at<'a>(&'a mut self, index: usize) -> OptionGuardMut<'a, Self::Inner>;
+}
+
+
+impl<T> SliceOptionExt for [Option<T>] {
+    type Inner = T;
+
+    #[track_caller]
+    fn steal_at<'a>(&'a mut self, index: usize) -> (OptionGuard<'a, T>, T) {
+        self[index].steal()
+    }
+
+    #[track_caller]
+    fn steal_mut_at<'a>(&'a mut self, index: usize) -> OptionGuardMut<'a, T> {
+        self[index].steal_mut()
+    }
+}
+
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    use StealFromNone;
+
+    #[test]
+    fn steal_from_vacant_index() {
+        let mut slots = [Some(1), None];
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            slots.steal_mut_at(1);
+        }))
+        .unwrap_err();
+
+        assert!(payload.downcast_ref::<StealFromNone>().unwrap().is_slot(&slots[1]));
+        assert_eq!(slots, [Some(1), None]);
+    }
+}
@@ -0,0 +1,208 @@
+use violation;
+use {EmptyOptionExt, OptionGuard, OptionGuardMut, StealError};
+
+
+/// An `Option<T>` which always holds a value, except while it is stolen. A `Slot` can only be
+/// made from a value, and offers only the steals and accessors which keep it full, so the "always
+/// present" invariant is part of its type rather than a convention. It has the same layout as an
+/// `Option<T>`, and converts to and from one for free.
+///
+/// The invariant can still be broken deliberately, by keeping a stolen value with
+/// `OptionGuardMut::into_inner` or `OptionGuard::defuse`. Every accessor then panics with a
+/// `StealFromNone`, and every `try_` accessor returns a `StealError`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::Slot;
+/// let mut state = Slot::new(String::from("idle"));
+///
+/// {
+///     let (guard, idle) = state.steal();
+///
+///     guard.restore(idle.replace("idle", "running"));
+/// }
+///
+/// state.steal_mut().push('!');
+///
+/// assert_eq!(state.get(), "running!");
+/// assert_eq!(state.into_option(), Some(String::from("running!")));
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Slot<T> {
+    value: Option<T>,
+}
+
+
+impl<T> Slot<T> {
+    /// Create a new slot holding a value.
+    pub const fn new(value: T) -> Slot<T> {
+        Slot { value: Some(value) }
+    }
+
+
+    /// Make a slot from an `Option`, if the `Option` holds a value.
+    pub fn from_option(value: Option<T>) -> Option<Slot<T>> {
+        if value.is_some() {
+            Some(Slot { value })
+        } else {
+            None
+        }
+    }
+
+
+    /// A reference to the value in the slot.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on an emptied slot, which the `strict` feature forbids; use `try_get`")
+    )]
+    #[track_caller]
+    pub fn get(&self) -> &T {
+        match self.value {
+            Some(ref value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        }
+    }
+
+
+    /// Like `Slot::get`, but returning a `StealError` if the slot was emptied instead of
+    /// panicking.
+    pub fn try_get(&self) -> Result<&T, StealError> {
+        self.value.as_ref().ok_or(StealError)
+    }
+
+
+    /// A mutable reference to the value in the slot.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on an emptied slot, which the `strict` feature forbids; use `try_get_mut`")
+    )]
+    #[track_caller]
+    pub fn get_mut(&mut self) -> &mut T {
+        match self.value {
+            Some(ref mut value) => value,
+            None => violation::steal_from_none::<T, _>(self),
+        }
+    }
+
+
+    /// Like `Slot::get_mut`, but returning a `StealError` if the slot was emptied instead of
+    /// panicking.
+    pub fn try_get_mut(&mut self) -> Result<&mut T, StealError> {
+        self.value.as_mut().ok_or(StealError)
+    }
+
+
+    /// Take the value out of the slot, providing a guard which panics if the value is not
+    /// returned.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on an emptied slot, which the `strict` feature forbids; use `try_steal`")
+    )]
+    #[track_caller]
+    pub fn steal<'a>(&'a mut self) -> (OptionGuard<'a, T>, T) {
+        self.value.steal()
+    }
+
+
+    /// Like `Slot::steal`, but returning a `StealError` if the slot was emptied instead of
+    /// panicking.
+    #[track_caller]
+    pub fn try_steal<'a>(&'a mut self) -> Result<(OptionGuard<'a, T>, T), StealError> {
+        self.value.try_steal()
+    }
+
+
+    /// Take the value out of the slot, providing a guard which returns the value when dropped.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on an emptied slot, which the `strict` feature forbids; use `try_steal_mut`")
+    )]
+    #[track_caller]
+    pub fn steal_mut<'a>(&'a mut self) -> OptionGuardMut<'a, T> {
+        self.value.steal_mut()
+    }
+
+
+    /// Like `Slot::steal_mut`, but returning a `StealError` if the slot was emptied instead of
+    /// panicking.
+    #[track_caller]
+    pub fn try_steal_mut<'a>(&'a mut self) -> Result<OptionGuardMut<'a, T>, StealError> {
+        self.value.try_steal_mut()
+    }
+
+
+    /// Consume the slot, returning its value.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics on an emptied slot, which the `strict` feature forbids; use `into_option`")
+    )]
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        match self.value {
+            Some(value) => value,
+            None => violation::steal_from_none::<T, _>(&self),
+        }
+    }
+
+
+    /// Consume the slot, returning its value as an `Option`.
+    pub fn into_option(self) -> Option<T> {
+        self.value
+    }
+}
+
+
+impl<T> From<T> for Slot<T> {
+    fn from(value: T) -> Slot<T> {
+        Slot::new(value)
+    }
+}
+
+
+impl<T> From<Slot<T>> for Option<T> {
+    fn from(slot: Slot<T>) -> Option<T> {
+        slot.value
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::mem;
+
+    #[test]
+    fn exactly_an_option() {
+        assert_eq!(mem::size_of::<Slot<String>>(), mem::size_of::<Option<String>>());
+
+        assert_eq!(Slot::from_option(Some(1)), Some(Slot::new(1)));
+        assert_eq!(Slot::<i32>::from_option(None), None);
+        assert_eq!(Option::from(Slot::from(1)), Some(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_from_emptied_slot() {
+        let mut slot = Slot::new(1);
+
+        slot.steal().0.defuse();
+        slot.get();
+    }
+
+    #[test]
+    fn try_accessors_on_emptied_slot() {
+        let mut slot = Slot::new(1);
+
+        *slot.try_get_mut().unwrap() += 1;
+        assert_eq!(slot.try_get(), Ok(&2));
+
+        slot.try_steal_mut().unwrap().into_inner();
+
+        assert_eq!(slot.try_get(), Err(StealError));
+        assert!(slot.try_steal().is_err());
+        assert!(slot.try_steal_mut().is_err());
+    }
+}
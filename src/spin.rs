@@ -0,0 +1,263 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::hint;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+
+struct State<T> {
+    value: Option<T>,
+    stolen: bool,
+}
+
+
+/// A slot shared between threads or cores, protected by a simple atomic spinlock rather than an
+/// OS mutex, for bare-metal targets with neither an operating system nor a `critical-section`
+/// implementation - multicore microcontrollers sharing a slot between cores, say. It is built
+/// on nothing but the `core` parts of `std`.
+///
+/// Like a `SharedSlot`, the lock is only held for the instant it takes to move the value in or
+/// out; the `SpinSlotGuard` owns the value while it is stolen, and returns it when dropped.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::SpinSlot;
+/// static BUFFER: SpinSlot<[u8; 4]> = SpinSlot::new([0; 4]);
+///
+/// {
+///     let mut buffer = BUFFER.try_steal().unwrap();
+///
+///     buffer[0] = 1;
+///
+///     // The other core finds the slot empty while we hold the buffer.
+///     assert!(BUFFER.try_steal().is_none());
+/// }
+///
+/// assert_eq!(BUFFER.steal_spin()[0], 1);
+/// ```
+pub struct SpinSlot<T> {
+    locked: AtomicBool,
+    state: UnsafeCell<State<T>>,
+}
+
+
+// The state is only ever accessed with the spinlock held.
+unsafe impl<T: Send> Sync for SpinSlot<T> {}
+
+
+impl<T> SpinSlot<T> {
+    /// Create a new slot holding a value.
+    pub const fn new(value: T) -> SpinSlot<T> {
+        SpinSlot::from_option(Some(value))
+    }
+
+
+    /// Create a new, vacant slot.
+    pub const fn empty() -> SpinSlot<T> {
+        SpinSlot::from_option(None)
+    }
+
+
+    const fn from_option(value: Option<T>) -> SpinSlot<T> {
+        SpinSlot {
+            locked: AtomicBool::new(false),
+            state: UnsafeCell::new(State {
+                value,
+                stolen: false,
+            }),
+        }
+    }
+
+
+    /// Run `f` on the state with the spinlock held. `f` must not panic, or the lock is never
+    /// released.
+    fn with_state<R, F: FnOnce(&mut State<T>) -> R>(&self, f: F) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+
+        let result = f(unsafe { &mut *self.state.get() });
+
+        self.locked.store(false, Ordering::Release);
+
+        result
+    }
+
+
+    /// Take the value out of the slot if it holds one right now, providing a guard which returns
+    /// the value when dropped unless consumed by `SpinSlotGuard::into_inner`.
+    pub fn try_steal<'a>(&'a self) -> Option<SpinSlotGuard<'a, T>> {
+        let value = self.with_state(|state| {
+            let value = state.value.take()?;
+            state.stolen = true;
+            Some(value)
+        })?;
+
+        Some(SpinSlotGuard {
+            slot: self,
+            value: Some(value),
+        })
+    }
+
+
+    /// Spin until the slot holds a value, and then take it.
+    pub fn steal_spin<'a>(&'a self) -> SpinSlotGuard<'a, T> {
+        loop {
+            if let Some(guard) = self.try_steal() {
+                return guard;
+            }
+
+            hint::spin_loop();
+        }
+    }
+
+
+    /// Spin until the slot holds a value, steal it, run `f` on it, and return it to the slot,
+    /// returning `f`'s result.
+    pub fn with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        f(&mut self.steal_spin())
+    }
+
+
+    /// Fill a vacant slot with a value. If the slot already holds a value, or its value is
+    /// currently stolen, the value is handed back.
+    pub fn put(&self, value: T) -> Result<(), T> {
+        self.with_state(|state| {
+            if state.stolen || state.value.is_some() {
+                return Err(value);
+            }
+
+            state.value = Some(value);
+
+            Ok(())
+        })
+    }
+
+
+    /// Returns `true` if the slot holds a value which is not currently stolen.
+    pub fn is_occupied(&self) -> bool {
+        self.with_state(|state| state.value.is_some())
+    }
+
+
+    /// Consume the slot, returning the value in it, if any.
+    pub fn into_inner(self) -> Option<T> {
+        self.state.into_inner().value
+    }
+
+
+    fn check_in(&self, value: Option<T>) {
+        self.with_state(|state| {
+            state.stolen = false;
+
+            if value.is_some() {
+                state.value = value;
+            }
+        })
+    }
+}
+
+
+impl<T> fmt::Debug for SpinSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (occupied, stolen) = self.with_state(|state| (state.value.is_some(), state.stolen));
+
+        f.debug_struct("SpinSlot")
+            .field("occupied", &occupied)
+            .field("stolen", &stolen)
+            .finish()
+    }
+}
+
+
+/// A value stolen from a `SpinSlot`. `SpinSlotGuard<T>` dereferences to a `T`, and the inner `T`
+/// can be moved out with `SpinSlotGuard::into_inner`, leaving the slot vacant. When dropped, the
+/// `SpinSlotGuard` returns the value to the slot.
+pub struct SpinSlotGuard<'a, T: 'a> {
+    slot: &'a SpinSlot<T>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> SpinSlotGuard<'a, T> {
+    /// Keep the value stolen from the slot and do not return it. The slot is left vacant, ready to
+    /// be refilled with `SpinSlot::put`.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for SpinSlotGuard<'a, T> {
+    fn drop(&mut self) {
+        self.slot.check_in(self.value.take());
+    }
+}
+
+
+impl<'a, T> Deref for SpinSlotGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for SpinSlotGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn put_and_steal() {
+        let slot = SpinSlot::empty();
+
+        assert!(slot.try_steal().is_none());
+        assert_eq!(slot.put(1), Ok(()));
+        assert_eq!(slot.put(2), Err(2));
+
+        let stolen = slot.try_steal().unwrap();
+        assert_eq!(slot.put(3), Err(3));
+        assert_eq!(stolen.into_inner(), 1);
+
+        assert_eq!(slot.put(4), Ok(()));
+        assert_eq!(slot.into_inner(), Some(4));
+    }
+
+    #[test]
+    fn cores_share_slot() {
+        let slot = Arc::new(SpinSlot::new(0));
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let slot = slot.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        *slot.steal_spin() += 1;
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(*slot.steal_spin(), 400);
+    }
+}
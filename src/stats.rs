@@ -0,0 +1,248 @@
+//! Per-call-site profiling of steals, enabled by the `stats` feature.
+//!
+//! Every steal made with `EmptyOptionExt::steal` or `EmptyOptionExt::steal_mut` records the
+//! source location it was made from. When its guard is restored, dropped or consumed, the time
+//! the value spent out of its slot is added to that location's totals, and `report` lists the
+//! totals for every call site, longest-holding first.
+//!
+//! # Examples
+//!
+//! ```
+//! # use empty_option::{stats, EmptyOptionExt};
+//! let mut thing = Some(5);
+//!
+//! for _ in 0..3 {
+//!     let (guard, five) = thing.steal();
+//!     guard.restore(five);
+//! }
+//!
+//! let site = stats::report()
+//!     .into_iter()
+//!     .find(|site| site.location().file() == file!())
+//!     .unwrap();
+//!
+//! assert!(site.steals() >= 3);
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use poison;
+
+
+type SiteKey = (&'static str, u32, u32);
+
+
+static SITES: Mutex<BTreeMap<SiteKey, SiteReport>> = Mutex::new(BTreeMap::new());
+
+
+fn sites() -> MutexGuard<'static, BTreeMap<SiteKey, SiteReport>> {
+    poison::lock(&SITES)
+}
+
+
+/// The steals made from a single call site, and how long they held their values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SiteReport {
+    location: &'static Location<'static>,
+    steals: u64,
+    total_held: Duration,
+    max_held: Duration,
+}
+
+
+impl SiteReport {
+    /// The source location the steals were made from.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+
+    /// The number of completed steals, not counting guards which are still outstanding.
+    pub fn steals(&self) -> u64 {
+        self.steals
+    }
+
+
+    /// The total time values stolen from this call site spent out of their slots.
+    pub fn total_held(&self) -> Duration {
+        self.total_held
+    }
+
+
+    /// The longest time a single value stolen from this call site spent out of its slot.
+    pub fn max_held(&self) -> Duration {
+        self.max_held
+    }
+
+
+    /// The average time a value stolen from this call site spent out of its slot.
+    pub fn mean_held(&self) -> Duration {
+        Duration::from_nanos((self.total_held.as_nanos() / self.steals as u128) as u64)
+    }
+}
+
+
+impl fmt::Display for SiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} steals, {:?} held in total, {:?} at most",
+            self.location, self.steals, self.total_held, self.max_held
+        )
+    }
+}
+
+
+/// The totals for every call site which has completed a steal, sorted by the total time values
+/// were held, longest first.
+pub fn report() -> Vec<SiteReport> {
+    let mut report: Vec<_> = sites().values().cloned().collect();
+
+    report.sort_by_key(|site| Reverse(site.total_held));
+    report
+}
+
+
+/// Forget the totals recorded so far.
+pub fn reset() {
+    sites().clear();
+}
+
+
+/// Times a single steal, recording it against its call site when dropped.
+pub(crate) struct Hold {
+    location: &'static Location<'static>,
+    start: Instant,
+}
+
+
+impl Hold {
+    #[track_caller]
+    pub(crate) fn start() -> Hold {
+        Hold {
+            location: Location::caller(),
+            start: Instant::now(),
+        }
+    }
+}
+
+
+impl Drop for Hold {
+    fn drop(&mut self) {
+        let held = self.start.elapsed();
+        let key = (self.location.file(), self.location.line(), self.location.column());
+
+        let mut sites = sites();
+        let site = sites.entry(key).or_insert(SiteReport {
+            location: self.location,
+            steals: 0,
+            total_held: Duration::ZERO,
+            max_held: Duration::ZERO,
+        });
+
+        site.steals += 1;
+        site.total_held += held;
+        site.max_held = site.max_held.max(held);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    use EmptyOptionExt;
+
+    fn site_of(line: u32) -> SiteReport {
+        report()
+            .into_iter()
+            .find(|site| site.location().file() == file!() && site.location().line() == line)
+            .unwrap()
+    }
+
+    #[test]
+    fn records_hold_durations() {
+        let mut thing = Some(5);
+        let line = line!() + 3;
+
+        for _ in 0..2 {
+            let stolen = thing.steal_mut();
+            thread::sleep(Duration::from_millis(5));
+            drop(stolen);
+        }
+
+        let site = site_of(line);
+
+        assert_eq!(site.steals(), 2);
+        assert!(site.max_held() >= Duration::from_millis(5));
+        assert!(site.total_held() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn restore_and_into_inner_are_recorded() {
+        let mut thing = Some(5);
+
+        let line = line!() + 1;
+        let (guard, five) = thing.steal();
+        guard.restore(five);
+
+        assert_eq!(site_of(line).steals(), 1);
+
+        let line = line!() + 1;
+        assert_eq!(thing.steal_mut().into_inner(), 5);
+
+        assert_eq!(site_of(line).steals(), 1);
+    }
+
+    #[test]
+    fn mean_of_many_steals() {
+        let site = SiteReport {
+            location: Location::caller(),
+            steals: 1 << 32,
+            total_held: Duration::from_secs(1 << 32),
+            max_held: Duration::from_secs(2),
+        };
+
+        assert_eq!(site.mean_held(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn into_guard_keeps_hold() {
+        let mut thing = Some(5);
+
+        let line = line!() + 1;
+        let stolen = thing.steal_mut();
+        let (guard, five) = stolen.into_guard();
+        guard.restore(five);
+
+        assert_eq!(site_of(line).steals(), 1);
+        assert!(report()
+            .iter()
+            .all(|site| site.location().file() != file!() || site.location().line() != line + 1));
+    }
+
+    #[test]
+    fn restore_mut_and_transfer_keep_hold() {
+        let mut thing = Some(5);
+        let mut other = None;
+
+        let line = line!() + 1;
+        let (guard, five) = thing.steal();
+        let guard = guard.transfer(&mut other);
+        drop(guard.restore_mut(five));
+
+        assert_eq!(site_of(line).steals(), 1);
+        assert!(report().iter().all(|site| {
+            let later = line + 1..=line + 2;
+            site.location().file() != file!() || !later.contains(&site.location().line())
+        }));
+        assert_eq!(other, Some(5));
+    }
+}
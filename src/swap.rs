@@ -0,0 +1,125 @@
+use std::mem;
+use std::panic::Location;
+use std::ptr;
+
+use {violation, OptionGuard};
+
+
+/// Two options which have had their values taken together by `EmptyOptionExt::swap_steal`. The
+/// guard panics when dropped unless both are refilled: with `SwapGuard::restore` or
+/// `SwapGuard::swap`, or one at a time with `SwapGuard::restore_first` or
+/// `SwapGuard::restore_second`, which hand back an `OptionGuard` for the other. Since the slots
+/// are named by the methods rather than by which guard is which, a value cannot be restored to
+/// the wrong slot by mistake.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::EmptyOptionExt;
+/// let mut front = Some(String::from("front"));
+/// let mut back = Some(String::from("back"));
+///
+/// let (guard, front_value, back_value) = front.swap_steal(&mut back);
+/// guard.swap(front_value, back_value);
+///
+/// assert_eq!((front.as_deref(), back.as_deref()), (Some("back"), Some("front")));
+/// ```
+pub struct SwapGuard<'a, T: 'a> {
+    first: &'a mut Option<T>,
+    second: &'a mut Option<T>,
+    location: &'static Location<'static>,
+}
+
+
+impl<'a, T> SwapGuard<'a, T> {
+    #[track_caller]
+    pub(crate) fn new(first: &'a mut Option<T>, second: &'a mut Option<T>) -> SwapGuard<'a, T> {
+        SwapGuard {
+            first,
+            second,
+            location: Location::caller(),
+        }
+    }
+
+
+    /// Restore each value to the option it was stolen from.
+    pub fn restore(self, first: T, second: T) {
+        let (first_slot, second_slot) = self.into_slots();
+
+        *first_slot = Some(first);
+        *second_slot = Some(second);
+    }
+
+
+    /// Restore each value to the other option: `first` to the second option, and `second` to the
+    /// first.
+    pub fn swap(self, first: T, second: T) {
+        self.restore(second, first);
+    }
+
+
+    /// Restore a value to the first option, leaving an `OptionGuard` for the second.
+    #[track_caller]
+    pub fn restore_first(self, obj: T) -> OptionGuard<'a, T> {
+        let (first, second) = self.into_slots();
+
+        *first = Some(obj);
+        OptionGuard::new(second)
+    }
+
+
+    /// Restore a value to the second option, leaving an `OptionGuard` for the first.
+    #[track_caller]
+    pub fn restore_second(self, obj: T) -> OptionGuard<'a, T> {
+        let (first, second) = self.into_slots();
+
+        *second = Some(obj);
+        OptionGuard::new(first)
+    }
+
+
+    fn into_slots(self) -> (&'a mut Option<T>, &'a mut Option<T>) {
+        let slots = unsafe { (ptr::read(&self.first), ptr::read(&self.second)) };
+        mem::forget(self);
+        slots
+    }
+}
+
+
+impl<'a, T> Drop for SwapGuard<'a, T> {
+    fn drop(&mut self) {
+        violation::raise(violation::leak_of::<T, _>(self.first, self.location, None));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use EmptyOptionExt;
+
+    #[test]
+    fn restore_one_at_a_time() {
+        let mut a = Some(1);
+        let mut b = Some(2);
+
+        let (guard, one, two) = a.swap_steal(&mut b);
+        guard.restore_second(one).restore(two);
+
+        assert_eq!((a, b), (Some(2), Some(1)));
+    }
+
+    #[test]
+    fn none_takes_nothing() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut a = Some(1);
+        let mut b = None;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            a.swap_steal(&mut b);
+        }));
+
+        assert!(result.is_err());
+        assert_eq!((a, b), (Some(1), None));
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+use std::ptr;
+
+use violation;
+
+
+/// Take a value out from behind a plain `&mut T`, leaving `T::default()` in its place, and
+/// providing a guard which puts the value back when dropped. No `Option` is needed to make room
+/// for the steal; the default stands in for the value until the guard returns it.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::take_guarded;
+/// struct Parser {
+///     tokens: Vec<String>,
+///     line: u32,
+/// }
+///
+/// let mut parser = Parser { tokens: vec![String::from("a")], line: 1 };
+///
+/// {
+///     let mut tokens = take_guarded(&mut parser.tokens);
+///
+///     // The rest of `parser` is free to be used while its tokens are out.
+///     parser.line += 1;
+///
+///     tokens.push(String::from("b"));
+/// }
+///
+/// assert_eq!(parser.tokens, ["a", "b"]);
+/// ```
+pub fn take_guarded<T: Default>(value: &mut T) -> TakeGuard<'_, T> {
+    let taken = mem::take(value);
+
+    TakeGuard {
+        origin: value,
+        value: Some(taken),
+    }
+}
+
+
+/// Take a value out from behind a plain `&mut T`, leaving `T::default()` in its place, and
+/// providing a guard which panics if the value is not restored - the `&mut T` counterpart of
+/// `EmptyOptionExt::steal`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::take_strict;
+/// let mut count = 5u32;
+///
+/// let (guard, five) = take_strict(&mut count);
+/// guard.restore(five + 1);
+///
+/// assert_eq!(count, 6);
+/// ```
+#[track_caller]
+pub fn take_strict<T: Default>(value: &mut T) -> (StrictTakeGuard<'_, T>, T) {
+    let taken = mem::take(value);
+
+    let guard = StrictTakeGuard {
+        origin: value,
+        location: Location::caller(),
+    };
+
+    (guard, taken)
+}
+
+
+/// Replace the value behind a `&mut T` with `f` applied to it, without needing a placeholder to
+/// stand in while `f` runs. If `f` panics there is no value to put back, so the process is
+/// aborted rather than let anyone observe the moved-out reference; use `take_in_place_or_recover`
+/// to refill it instead.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::take_in_place;
+/// enum Door {
+///     Open(String),
+///     Closed(String),
+/// }
+///
+/// let mut door = Door::Closed(String::from("front"));
+///
+/// take_in_place(&mut door, |door| match door {
+///     Door::Closed(name) => Door::Open(name),
+///     open => open,
+/// });
+///
+/// assert!(matches!(door, Door::Open(ref name) if name == "front"));
+/// ```
+pub fn take_in_place<T, F: FnOnce(T) -> T>(value: &mut T, f: F) {
+    struct AbortOnUnwind;
+
+    impl Drop for AbortOnUnwind {
+        fn drop(&mut self) {
+            violation::abort(&"the closure passed to `take_in_place` panicked");
+        }
+    }
+
+    let abort = AbortOnUnwind;
+
+    // If `f` panics, `abort` is dropped while unwinding and the moved-out value is never seen.
+    unsafe {
+        let replacement = f(ptr::read(value));
+        ptr::write(value, replacement);
+    }
+
+    mem::forget(abort);
+}
+
+
+/// Like `take_in_place`, but if `f` panics the reference is refilled with `recover()` before the
+/// panic carries on unwinding. If `recover` panics as well, the process aborts.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::take_in_place_or_recover;
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// let mut words = vec![String::from("a")];
+///
+/// let result = panic::catch_unwind(AssertUnwindSafe(|| {
+///     take_in_place_or_recover(&mut words, Vec::new, |mut words| {
+///         words.push(String::from("b"));
+///         panic!("lost the words")
+///     });
+/// }));
+///
+/// assert!(result.is_err());
+/// assert!(words.is_empty());
+/// ```
+pub fn take_in_place_or_recover<T, R, F>(value: &mut T, recover: R, f: F)
+where
+    R: FnOnce() -> T,
+    F: FnOnce(T) -> T,
+{
+    struct RecoverOnUnwind<T, R: FnOnce() -> T> {
+        slot: *mut T,
+        recover: Option<R>,
+    }
+
+    impl<T, R: FnOnce() -> T> Drop for RecoverOnUnwind<T, R> {
+        fn drop(&mut self) {
+            if let Some(recover) = self.recover.take() {
+                unsafe { ptr::write(self.slot, recover()) };
+            }
+        }
+    }
+
+    let slot: *mut T = value;
+    let mut guard = RecoverOnUnwind {
+        slot,
+        recover: Some(recover),
+    };
+
+    unsafe {
+        let replacement = f(ptr::read(slot));
+        ptr::write(slot, replacement);
+    }
+
+    guard.recover = None;
+}
+
+
+/// A value taken by `take_guarded`, which left `T::default()` behind. `TakeGuard<T>` dereferences
+/// to a `T`, and the inner `T` can be moved out with `TakeGuard::into_inner`. When dropped, the
+/// `TakeGuard` moves the value back, replacing the default.
+pub struct TakeGuard<'a, T: 'a> {
+    origin: &'a mut T,
+    value: Option<T>,
+}
+
+
+impl<'a, T> TakeGuard<'a, T> {
+    /// Keep the taken value, leaving the default in its place.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for TakeGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            *self.origin = value;
+        }
+    }
+}
+
+
+impl<'a, T> Deref for TakeGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for TakeGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+impl<'a, T: fmt::Debug> fmt::Debug for TakeGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TakeGuard").field(&**self).finish()
+    }
+}
+
+
+/// A reference whose value was taken by `take_strict`, which left `T::default()` behind. The value
+/// must be put back with `StrictTakeGuard::restore`; dropping the guard without a restore panics
+/// with a `GuardLeaked`, leaving the default in place.
+pub struct StrictTakeGuard<'a, T: 'a> {
+    origin: &'a mut T,
+    location: &'static Location<'static>,
+}
+
+
+impl<'a, T> StrictTakeGuard<'a, T> {
+    /// Restore a taken value, replacing the default.
+    pub fn restore(self, obj: T) {
+        *self.origin = obj;
+        mem::forget(self);
+    }
+}
+
+
+impl<'a, T> Drop for StrictTakeGuard<'a, T> {
+    fn drop(&mut self) {
+        violation::raise(violation::leak_of::<T, _>(self.origin, self.location, None));
+    }
+}
+
+
+impl<'a, T> fmt::Debug for StrictTakeGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The guard holds only the default, so `T` needn't be `Debug`.
+        f.debug_struct("StrictTakeGuard")
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_taken_value() {
+        let mut name = String::from("kept");
+
+        assert_eq!(take_guarded(&mut name).into_inner(), "kept");
+        assert_eq!(name, "");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn strict_leak_leaves_default() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        use GuardLeaked;
+
+        let mut count = 5;
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (_, _) = take_strict(&mut count);
+        }))
+        .unwrap_err();
+
+        assert_eq!(payload.downcast_ref::<GuardLeaked>().unwrap().type_name(), "i32");
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn take_in_place_moves_value() {
+        let mut name = String::from("in place");
+
+        take_in_place(&mut name, |name| name + "!");
+        take_in_place_or_recover(&mut name, String::new, |name| name.to_uppercase());
+
+        assert_eq!(name, "IN PLACE!");
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+
+use {poison, violation};
+
+
+/// Something which happened to a `TestSlot`, as recorded by `TestSlot::events`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SlotEvent<T> {
+    /// A value was stolen from the slot.
+    Steal(T),
+
+    /// A stolen value, possibly modified, was returned to the slot by dropping its guard.
+    Restore(T),
+
+    /// A stolen value was kept with `TestSlotGuard::into_inner`, leaving the slot vacant.
+    Consume(T),
+
+    /// A vacant slot was filled with `TestSlot::put`.
+    Put(T),
+}
+
+
+struct State<T> {
+    value: Option<T>,
+    stolen: bool,
+    events: Vec<SlotEvent<T>>,
+}
+
+
+/// A test double for a slot, which records every steal, restore, consume and put along with the
+/// values involved. Hand one to code which takes a slot as a dependency, then check that it
+/// took and returned values correctly with `TestSlot::assert_sequence` and
+/// `TestSlot::assert_balanced`.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{SlotEvent, TestSlot};
+/// fn bump(counter: &TestSlot<u32>) {
+///     *counter.steal() += 1;
+/// }
+///
+/// let counter = TestSlot::new(1);
+///
+/// bump(&counter);
+/// bump(&counter);
+///
+/// counter.assert_balanced();
+/// counter.assert_sequence(&[
+///     SlotEvent::Steal(1),
+///     SlotEvent::Restore(2),
+///     SlotEvent::Steal(2),
+///     SlotEvent::Restore(3),
+/// ]);
+/// ```
+pub struct TestSlot<T> {
+    state: Mutex<State<T>>,
+}
+
+
+impl<T: Clone + fmt::Debug> TestSlot<T> {
+    /// Create a new slot holding a value.
+    pub const fn new(value: T) -> TestSlot<T> {
+        TestSlot::from_option(Some(value))
+    }
+
+
+    /// Create a new, vacant slot.
+    pub const fn empty() -> TestSlot<T> {
+        TestSlot::from_option(None)
+    }
+
+
+    const fn from_option(value: Option<T>) -> TestSlot<T> {
+        TestSlot {
+            state: Mutex::new(State {
+                value,
+                stolen: false,
+                events: Vec::new(),
+            }),
+        }
+    }
+
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        poison::lock(&self.state)
+    }
+
+
+    /// Take the value out of the slot, providing a guard which returns the value when dropped
+    /// unless consumed by `TestSlotGuard::into_inner`. Panics with a `StealFromNone` if the slot
+    /// holds no value, or with an `AlreadyStolen` if its value is currently stolen by another
+    /// guard.
+    #[track_caller]
+    pub fn steal<'a>(&'a self) -> TestSlotGuard<'a, T> {
+        let mut state = self.lock();
+
+        if let Some(guard) = self.take(&mut state) {
+            return guard;
+        }
+
+        let stolen = state.stolen;
+        drop(state);
+
+        if stolen {
+            violation::already_stolen::<T, _>(self)
+        } else {
+            violation::steal_from_none::<T, _>(self)
+        }
+    }
+
+
+    /// Take the value out of the slot if it holds one right now.
+    pub fn try_steal<'a>(&'a self) -> Option<TestSlotGuard<'a, T>> {
+        self.take(&mut self.lock())
+    }
+
+
+    fn take<'a>(&'a self, state: &mut State<T>) -> Option<TestSlotGuard<'a, T>> {
+        let value = state.value.take()?;
+        state.stolen = true;
+        state.events.push(SlotEvent::Steal(value.clone()));
+
+        Some(TestSlotGuard {
+            slot: self,
+            value: Some(value),
+        })
+    }
+
+
+    /// Fill a vacant slot with a value. If the slot already holds a value, or its value is
+    /// currently stolen, the value is handed back and nothing is recorded.
+    pub fn put(&self, value: T) -> Result<(), T> {
+        let mut state = self.lock();
+
+        if state.stolen || state.value.is_some() {
+            return Err(value);
+        }
+
+        state.events.push(SlotEvent::Put(value.clone()));
+        state.value = Some(value);
+
+        Ok(())
+    }
+
+
+    /// Everything which has happened to the slot so far, oldest first.
+    pub fn events(&self) -> Vec<SlotEvent<T>> {
+        self.lock().events.clone()
+    }
+
+
+    /// Panic unless the events recorded so far are exactly `expected`.
+    pub fn assert_sequence(&self, expected: &[SlotEvent<T>])
+    where
+        T: PartialEq,
+    {
+        let events = self.events();
+
+        assert!(
+            events[..] == *expected,
+            "unexpected slot events\n  expected: {:?}\n    actual: {:?}",
+            expected,
+            events
+        );
+    }
+
+
+    /// Panic unless every steal so far was followed by a restore, before the next steal: no value
+    /// was consumed, and none is stolen right now.
+    pub fn assert_balanced(&self) {
+        let state = self.lock();
+        let mut stolen = false;
+
+        for event in &state.events {
+            let balanced = match *event {
+                SlotEvent::Steal(_) => !stolen,
+                SlotEvent::Restore(_) => stolen,
+                SlotEvent::Consume(_) => false,
+                SlotEvent::Put(_) => !stolen,
+            };
+
+            assert!(balanced, "unbalanced slot event {:?} in {:?}", event, state.events);
+
+            stolen = matches!(*event, SlotEvent::Steal(_));
+        }
+
+        assert!(!stolen, "slot value still stolen after {:?}", state.events);
+    }
+
+
+    /// Consume the slot, returning the value in it, if any.
+    pub fn into_inner(self) -> Option<T> {
+        poison::into_inner(self.state).value
+    }
+
+
+    fn check_in(&self, event: SlotEvent<T>, value: Option<T>) {
+        let mut state = self.lock();
+
+        state.stolen = false;
+        state.value = value;
+        state.events.push(event);
+    }
+}
+
+
+impl<T> fmt::Debug for TestSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = poison::lock(&self.state);
+
+        f.debug_struct("TestSlot")
+            .field("occupied", &state.value.is_some())
+            .field("stolen", &state.stolen)
+            .field("events", &state.events.len())
+            .finish()
+    }
+}
+
+
+/// A value stolen from a `TestSlot`. `TestSlotGuard<T>` dereferences to a `T`, and the inner `T`
+/// can be moved out with `TestSlotGuard::into_inner`, leaving the slot vacant. When dropped, the
+/// `TestSlotGuard` returns the value to the slot. Either way, the slot records it.
+pub struct TestSlotGuard<'a, T: 'a + Clone + fmt::Debug> {
+    slot: &'a TestSlot<T>,
+    value: Option<T>,
+}
+
+
+impl<'a, T: Clone + fmt::Debug> TestSlotGuard<'a, T> {
+    /// Keep the value stolen from the slot and do not return it. The slot is left vacant, ready to
+    /// be refilled with `TestSlot::put`.
+    pub fn into_inner(mut self) -> T {
+        let value = self.value.take().unwrap();
+
+        self.slot.check_in(SlotEvent::Consume(value.clone()), None);
+        value
+    }
+}
+
+
+impl<'a, T: Clone + fmt::Debug> Drop for TestSlotGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.slot.check_in(SlotEvent::Restore(value.clone()), Some(value));
+        }
+    }
+}
+
+
+impl<'a, T: Clone + fmt::Debug> Deref for TestSlotGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T: Clone + fmt::Debug> DerefMut for TestSlotGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::panic::{self, AssertUnwindSafe};
+
+    use {AlreadyStolen, StealFromNone};
+
+    #[test]
+    fn records_consume_and_put() {
+        let slot = TestSlot::new(String::from("a"));
+
+        assert_eq!(slot.steal().into_inner(), "a");
+        assert!(slot.try_steal().is_none());
+        assert_eq!(slot.put(String::from("b")), Ok(()));
+
+        slot.assert_sequence(&[
+            SlotEvent::Steal(String::from("a")),
+            SlotEvent::Consume(String::from("a")),
+            SlotEvent::Put(String::from("b")),
+        ]);
+    }
+
+    #[test]
+    fn unbalanced_steals() {
+        let consumed = TestSlot::new(1);
+        consumed.steal().into_inner();
+
+        let outstanding = TestSlot::new(1);
+        let _guard = outstanding.steal();
+
+        for slot in &[&consumed, &outstanding] {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| slot.assert_balanced()));
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn steal_while_stolen() {
+        let slot = TestSlot::new(1);
+        let _guard = slot.steal();
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            slot.steal();
+        }))
+        .unwrap_err();
+
+        assert!(payload.downcast_ref::<AlreadyStolen>().unwrap().is_slot(&slot));
+        assert!(payload.downcast_ref::<StealFromNone>().is_none());
+    }
+}
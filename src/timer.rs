@@ -0,0 +1,125 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, Once};
+use std::task::Waker;
+use std::thread;
+use std::time::Instant;
+
+use poison;
+
+
+struct TimerState {
+    waker: Waker,
+    fired: bool,
+}
+
+
+/// Wakes a `Waker` once a deadline has passed, so that timeouts and lease deadlines work without
+/// any particular executor's timer or a thread of their own. Every pending timer is kept in
+/// `TIMERS`, and a single thread shared by all of them sleeps until the earliest deadline.
+pub(crate) struct Timer {
+    deadline: Instant,
+    state: Mutex<TimerState>,
+}
+
+
+/// The timers which have yet to fire, and a condition variable to tell the timer thread when one
+/// is added.
+struct TimerQueue {
+    timers: Mutex<Vec<Arc<Timer>>>,
+    added: Condvar,
+}
+
+
+static TIMERS: TimerQueue = TimerQueue {
+    timers: Mutex::new(Vec::new()),
+    added: Condvar::new(),
+};
+
+
+static TIMER_THREAD: Once = Once::new();
+
+
+impl TimerQueue {
+    fn lock(&self) -> MutexGuard<'_, Vec<Arc<Timer>>> {
+        poison::lock(&self.timers)
+    }
+
+
+    fn run(&self) {
+        let mut timers = self.lock();
+
+        loop {
+            let now = Instant::now();
+
+            timers.retain(|timer| {
+                if timer.deadline > now {
+                    return true;
+                }
+
+                timer.fire();
+                false
+            });
+
+            timers = match timers.iter().map(|timer| timer.deadline).min() {
+                Some(next) => poison::wait_timeout(&self.added, timers, next - now),
+                None => poison::wait(&self.added, timers),
+            };
+        }
+    }
+}
+
+
+impl Timer {
+    pub(crate) fn start(deadline: Instant, waker: &Waker) -> Arc<Timer> {
+        let timer = Arc::new(Timer {
+            deadline,
+            state: Mutex::new(TimerState {
+                waker: waker.clone(),
+                fired: false,
+            }),
+        });
+
+        TIMER_THREAD.call_once(|| {
+            thread::spawn(|| TIMERS.run());
+        });
+
+        TIMERS.lock().push(timer.clone());
+        TIMERS.added.notify_one();
+
+        timer
+    }
+
+
+    fn lock(&self) -> MutexGuard<'_, TimerState> {
+        poison::lock(&self.state)
+    }
+
+
+    fn fire(&self) {
+        let mut state = self.lock();
+
+        state.fired = true;
+        state.waker.wake_by_ref();
+    }
+
+
+    pub(crate) fn set_waker(&self, waker: &Waker) {
+        let mut state = self.lock();
+
+        if state.fired {
+            waker.wake_by_ref();
+        } else {
+            state.waker.clone_from(waker);
+        }
+    }
+
+
+    pub(crate) fn cancel(self: &Arc<Timer>) {
+        TIMERS.lock().retain(|timer| !Arc::ptr_eq(timer, self));
+    }
+
+
+    #[cfg(test)]
+    pub(crate) fn is_queued(self: &Arc<Timer>) -> bool {
+        TIMERS.lock().iter().any(|queued| Arc::ptr_eq(queued, self))
+    }
+}
@@ -0,0 +1,282 @@
+use std::ops::{Deref, DerefMut};
+
+use violation;
+
+
+/// A tuple of values, each of which can be stolen from and restored to its own `Option`.
+///
+/// Implemented for tuples of up to twelve values, and used by `StealTuple` to take a whole group
+/// of slots at once.
+pub trait RestoreTuple<'a>: Sized {
+    /// The tuple of `&mut Option`s the values are stolen from.
+    type Slots;
+
+    /// Take the values out of the slots. If any slot is empty, panics without taking anything.
+    fn take_from(slots: &mut Self::Slots) -> Self;
+
+    /// Move the values back into their slots.
+    fn restore_into(self, slots: Self::Slots);
+
+    /// Report that the values were never restored to the slots.
+    fn leaked(slots: &Self::Slots) -> !;
+}
+
+
+/// Extension trait providing `steal_tuple` and `steal_tuple_mut` on groups of options: either a
+/// `&mut` to a tuple of `Option`s, or a tuple of `&mut Option`s, of up to twelve elements.
+/// A whole group of slots, each of a different type, can then be taken and restored together
+/// through one combined guard.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::StealTuple;
+/// let mut slots = (Some(5), Some(String::from("five")));
+///
+/// {
+///     let (guard, (number, name)) = slots.steal_tuple();
+///
+///     assert_eq!((number, name.as_str()), (5, "five"));
+///
+///     guard.restore((number + 1, name + "!"));
+/// }
+///
+/// assert_eq!(slots, (Some(6), Some(String::from("five!"))));
+///
+/// let mut a = Some(1);
+/// let mut b = Some('b');
+///
+/// {
+///     let mut stolen = (&mut a, &mut b).steal_tuple_mut();
+///
+///     stolen.0 += 1;
+/// }
+///
+/// assert_eq!((a, b), (Some(2), Some('b')));
+/// ```
+pub trait StealTuple<'a>: Sized {
+    type Values: RestoreTuple<'a>;
+
+    /// The slots making up the group.
+    fn into_slots(self) -> <Self::Values as RestoreTuple<'a>>::Slots;
+
+
+    /// Take the values out of every option in the group, providing a guard which panics if the
+    /// values are not returned. Panics, without taking anything, if any option is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics if any option is `None`, which the `strict` feature forbids")
+    )]
+    #[track_caller]
+    fn steal_tuple(self) -> (TupleGuard<'a, Self::Values>, Self::Values) {
+        let mut slots = self.into_slots();
+        let values = Self::Values::take_from(&mut slots);
+
+        (TupleGuard { slots: Some(slots) }, values)
+    }
+
+
+    /// Take the values out of every option in the group, providing a guard which returns them
+    /// unless consumed by `TupleGuardMut::into_inner`. Panics, without taking anything, if any
+    /// option is `None`.
+    #[cfg_attr(
+        feature = "strict",
+        deprecated(note = "panics if any option is `None`, which the `strict` feature forbids")
+    )]
+    #[track_caller]
+    fn steal_tuple_mut(self) -> TupleGuardMut<'a, Self::Values> {
+        let mut slots = self.into_slots();
+        let values = Self::Values::take_from(&mut slots);
+
+        TupleGuardMut {
+            slots: Some(slots),
+            values: Some(values),
+        }
+    }
+}
+
+
+/// A group of options which have had their values taken by `StealTuple::steal_tuple`. Like an
+/// `OptionGuard`, a `TupleGuard` panics when dropped unless the values are moved back in with
+/// `TupleGuard::restore`.
+pub struct TupleGuard<'a, V: RestoreTuple<'a>> {
+    slots: Option<V::Slots>,
+}
+
+
+impl<'a, V: RestoreTuple<'a>> TupleGuard<'a, V> {
+    /// Restore every stolen value to its `Option`.
+    pub fn restore(mut self, values: V) {
+        values.restore_into(self.slots.take().unwrap());
+    }
+}
+
+
+impl<'a, V: RestoreTuple<'a>> Drop for TupleGuard<'a, V> {
+    fn drop(&mut self) {
+        if let Some(ref slots) = self.slots {
+            V::leaked(slots);
+        }
+    }
+}
+
+
+/// Values taken from a group of options by `StealTuple::steal_tuple_mut`. Like an
+/// `OptionGuardMut`, a `TupleGuardMut<V>` dereferences to the tuple of values, and moves each value
+/// back into its `Option` when dropped, unless consumed with `TupleGuardMut::into_inner`.
+pub struct TupleGuardMut<'a, V: RestoreTuple<'a>> {
+    slots: Option<V::Slots>,
+    values: Option<V>,
+}
+
+
+impl<'a, V: RestoreTuple<'a>> TupleGuardMut<'a, V> {
+    /// Keep the values stolen from the options and do not return them.
+    pub fn into_inner(mut self) -> V {
+        self.values.take().unwrap()
+    }
+}
+
+
+impl<'a, V: RestoreTuple<'a>> Drop for TupleGuardMut<'a, V> {
+    fn drop(&mut self) {
+        if let (Some(slots), Some(values)) = (self.slots.take(), self.values.take()) {
+            values.restore_into(slots);
+        }
+    }
+}
+
+
+impl<'a, V: RestoreTuple<'a>> Deref for TupleGuardMut<'a, V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.values.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, V: RestoreTuple<'a>> DerefMut for TupleGuardMut<'a, V> {
+    fn deref_mut(&mut self) -> &mut V {
+        self.values.as_mut().unwrap()
+    }
+}
+
+
+macro_rules! tuple_impls {
+    ($(($A:ident $a:tt $(, $T:ident $idx:tt)*))+) => {$(
+        impl<'a, $A: 'a $(, $T: 'a)*> RestoreTuple<'a> for ($A, $($T,)*) {
+            type Slots = (&'a mut Option<$A>, $(&'a mut Option<$T>,)*);
+
+            #[track_caller]
+            fn take_from(slots: &mut Self::Slots) -> Self {
+                if slots.$a.is_none() {
+                    violation::steal_from_none::<$A, _>(&*slots.$a);
+                }
+
+                $(
+                    if slots.$idx.is_none() {
+                        violation::steal_from_none::<$T, _>(&*slots.$idx);
+                    }
+                )*
+
+                (slots.$a.take().unwrap(), $(slots.$idx.take().unwrap(),)*)
+            }
+
+            fn restore_into(self, slots: Self::Slots) {
+                *slots.$a = Some(self.$a);
+                $(*slots.$idx = Some(self.$idx);)*
+            }
+
+            fn leaked(slots: &Self::Slots) -> ! {
+                violation::guard_leaked::<$A, _>(&*slots.$a)
+            }
+        }
+
+
+        impl<'a, $A: 'a $(, $T: 'a)*> StealTuple<'a> for &'a mut (Option<$A>, $(Option<$T>,)*) {
+            type Values = ($A, $($T,)*);
+
+            fn into_slots(self) -> <Self::Values as RestoreTuple<'a>>::Slots {
+                (&mut self.$a, $(&mut self.$idx,)*)
+            }
+        }
+
+
+        impl<'a, $A: 'a $(, $T: 'a)*> StealTuple<'a>
+            for (&'a mut Option<$A>, $(&'a mut Option<$T>,)*)
+        {
+            type Values = ($A, $($T,)*);
+
+            fn into_slots(self) -> <Self::Values as RestoreTuple<'a>>::Slots {
+                self
+            }
+        }
+    )+};
+}
+
+
+tuple_impls! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steal_and_restore_twelve() {
+        let mut slots = (
+            Some(0u8), Some(1u16), Some(2u32), Some(3u64), Some(4i8), Some(5i16),
+            Some(6i32), Some(7i64), Some('8'), Some("9"), Some(10usize), Some(11isize),
+        );
+
+        {
+            let mut stolen = slots.steal_tuple_mut();
+
+            stolen.0 += 1;
+            stolen.11 += 1;
+        }
+
+        assert_eq!(slots.0, Some(1));
+        assert_eq!(slots.11, Some(12));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn none_takes_nothing() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        use StealFromNone;
+
+        let mut a = Some(1);
+        let mut b: Option<String> = None;
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            (&mut a, &mut b).steal_tuple_mut().into_inner();
+        })).unwrap_err();
+
+        assert!(payload.downcast_ref::<StealFromNone>().unwrap().is_slot(&b));
+        assert_eq!(a, Some(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tuple_guard_leaked() {
+        let mut slots = (Some(1), Some(2));
+
+        let (_, _) = slots.steal_tuple();
+    }
+}
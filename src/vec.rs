@@ -0,0 +1,117 @@
+use alloc::vec::Vec;
+use std::ops::{Deref, DerefMut};
+
+
+/// Extension trait providing `pop_guard` on `Vec`s.
+pub trait VecStealExt {
+    type Item;
+
+    /// Pop the last element, providing a guard which pushes it back when dropped unless consumed
+    /// by `PopGuard::into_inner`. Returns `None` if the vector is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use empty_option::VecStealExt;
+    /// let mut scopes = vec![vec!["global"], vec!["local"]];
+    ///
+    /// {
+    ///     let mut top = scopes.pop_guard().unwrap();
+    ///
+    ///     // The rest of the stack is still there, underneath.
+    ///     assert_eq!(top.rest(), [vec!["global"]]);
+    ///
+    ///     top.push("shadowed");
+    /// }
+    ///
+    /// assert_eq!(scopes, [vec!["global"], vec!["local", "shadowed"]]);
+    ///
+    /// // Consume the guard to pop for good.
+    /// assert_eq!(scopes.pop_guard().unwrap().into_inner(), ["local", "shadowed"]);
+    /// assert_eq!(scopes.len(), 1);
+    /// ```
+    fn pop_guard<'a>(&'a mut self) -> Option<PopGuard<'a, Self::Item>>;
+}
+
+
+impl<T> VecStealExt for Vec<T> {
+    type Item = T;
+
+    fn pop_guard<'a>(&'a mut self) -> Option<PopGuard<'a, T>> {
+        let value = self.pop()?;
+
+        Some(PopGuard {
+            origin: self,
+            value: Some(value),
+        })
+    }
+}
+
+
+/// The last element of a `Vec`, popped by `VecStealExt::pop_guard`. `PopGuard<T>` dereferences to
+/// a `T`, and the inner `T` can be moved out with `PopGuard::into_inner`. When dropped, the
+/// `PopGuard` pushes the element back onto the end of the vector.
+pub struct PopGuard<'a, T: 'a> {
+    origin: &'a mut Vec<T>,
+    value: Option<T>,
+}
+
+
+impl<'a, T> PopGuard<'a, T> {
+    /// The rest of the vector, without the popped element.
+    pub fn rest(&self) -> &[T] {
+        self.origin
+    }
+
+
+    /// Keep the popped element, leaving it off the vector.
+    pub fn into_inner(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+
+impl<'a, T> Drop for PopGuard<'a, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.origin.push(value);
+        }
+    }
+}
+
+
+impl<'a, T> Deref for PopGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+
+impl<'a, T> DerefMut for PopGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pop_and_push_back() {
+        let mut stack = vec![1, 2, 3];
+
+        {
+            let mut top = stack.pop_guard().unwrap();
+
+            assert_eq!(top.rest(), [1, 2]);
+            *top *= 10;
+        }
+
+        assert_eq!(stack, [1, 2, 30]);
+        assert!(Vec::<i32>::new().pop_guard().is_none());
+    }
+}
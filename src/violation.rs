@@ -0,0 +1,460 @@
+use std::any::{self, Any};
+#[cfg(feature = "backtrace")]
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::panic;
+use std::panic::Location;
+#[cfg(feature = "std")]
+use std::process;
+
+
+/// The panic payload raised when a guard which requires its value to be restored (such as an
+/// `OptionGuard`) is dropped without a value being restored.
+///
+/// With the `std` feature, panics raised by this crate carry typed payloads rather than strings,
+/// so code running behind a `catch_unwind` boundary can downcast the payload to tell which
+/// contract was violated and by which slot. The default panic hook prints these payloads only as
+/// `Box<dyn Any>`; see the crate docs and `violation_message` for printing them in full.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// # use empty_option::{EmptyOptionExt, GuardLeaked};
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// let mut thing = Some(5);
+///
+/// let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+///     let (_, _) = thing.steal();
+/// })).unwrap_err();
+///
+/// let leaked = payload.downcast_ref::<GuardLeaked>().unwrap();
+///
+/// assert!(leaked.is_slot(&thing));
+/// assert_eq!(leaked.type_name(), "i32");
+/// assert!(leaked.to_string().contains("stolen at"));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GuardLeaked {
+    type_name: &'static str,
+    slot: usize,
+    location: Option<&'static Location<'static>>,
+    context: Option<&'static str>,
+}
+
+
+impl GuardLeaked {
+    fn new<T, S>(slot: &S) -> GuardLeaked {
+        GuardLeaked {
+            type_name: any::type_name::<T>(),
+            slot: slot as *const S as usize,
+            location: None,
+            context: None,
+        }
+    }
+
+
+    /// The name of the type of the value which was never restored.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+
+    /// The address of the slot which was left without a value.
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+
+    /// Returns `true` if the given slot is the one which was left without a value.
+    pub fn is_slot<S>(&self, slot: &S) -> bool {
+        self.slot == slot as *const S as usize
+    }
+
+
+    /// Where the leaked value was stolen, for guards which record it (such as `OptionGuard`).
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+
+
+    /// The context given to `EmptyOptionExt::steal_with_context`, if the value was stolen with it.
+    pub fn context(&self) -> Option<&'static str> {
+        self.context
+    }
+}
+
+
+impl fmt::Display for GuardLeaked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`Some` value was never restored to a victimized Option! (`{}` slot at {:#x}",
+            self.type_name, self.slot
+        )?;
+
+        if let Some(location) = self.location {
+            write!(f, ", stolen at {}", location)?;
+        }
+
+        f.write_str(")")?;
+
+        match self.context {
+            Some(context) => write!(f, ": {}", context),
+            None => Ok(()),
+        }
+    }
+}
+
+
+impl Error for GuardLeaked {}
+
+
+/// The panic payload raised when attempting to steal a value from a slot which is empty.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// # use empty_option::{EmptyOptionExt, StealFromNone};
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// let mut thing: Option<i32> = None;
+///
+/// let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+///     thing.steal_mut();
+/// })).unwrap_err();
+///
+/// assert!(payload.downcast_ref::<StealFromNone>().unwrap().is_slot(&thing));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StealFromNone {
+    type_name: &'static str,
+    slot: usize,
+    context: Option<&'static str>,
+}
+
+
+impl StealFromNone {
+    fn new<T, S>(slot: &S, context: Option<&'static str>) -> StealFromNone {
+        StealFromNone {
+            type_name: any::type_name::<T>(),
+            slot: slot as *const S as usize,
+            context,
+        }
+    }
+
+
+    /// The name of the type of the value which was to be stolen.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+
+    /// The address of the empty slot which was stolen from.
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+
+    /// Returns `true` if the given slot is the one which was stolen from.
+    pub fn is_slot<S>(&self, slot: &S) -> bool {
+        self.slot == slot as *const S as usize
+    }
+
+
+    /// The context given to `EmptyOptionExt::steal_with_context`, if the steal was made with it.
+    pub fn context(&self) -> Option<&'static str> {
+        self.context
+    }
+}
+
+
+impl fmt::Display for StealFromNone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "attempted to steal from None (`{}` slot at {:#x})",
+            self.type_name, self.slot
+        )?;
+
+        match self.context {
+            Some(context) => write!(f, ": {}", context),
+            None => Ok(()),
+        }
+    }
+}
+
+
+impl Error for StealFromNone {}
+
+
+/// The panic payload raised when attempting to transfer a guard's obligation to a slot which
+/// already holds a value, with `OptionGuard::transfer`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// # use empty_option::{EmptyOptionExt, TransferToOccupied};
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// let mut from = Some(1);
+/// let mut to = Some(2);
+///
+/// let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+///     let (guard, _) = from.steal();
+///     guard.transfer(&mut to);
+/// })).unwrap_err();
+///
+/// assert!(payload.downcast_ref::<TransferToOccupied>().unwrap().is_slot(&to));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TransferToOccupied {
+    type_name: &'static str,
+    slot: usize,
+    context: Option<&'static str>,
+}
+
+
+impl TransferToOccupied {
+    /// The name of the type of the value which the guard was waiting for.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+
+    /// The address of the occupied slot which the guard was to be transferred to.
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+
+    /// Returns `true` if the given slot is the one which the guard was to be transferred to.
+    pub fn is_slot<S>(&self, slot: &S) -> bool {
+        self.slot == slot as *const S as usize
+    }
+
+
+    /// The context given to `EmptyOptionExt::steal_with_context`, if the steal was made with it.
+    pub fn context(&self) -> Option<&'static str> {
+        self.context
+    }
+}
+
+
+impl fmt::Display for TransferToOccupied {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "attempted to transfer a guard to an occupied Option (`{}` slot at {:#x})",
+            self.type_name, self.slot
+        )?;
+
+        match self.context {
+            Some(context) => write!(f, ": {}", context),
+            None => Ok(()),
+        }
+    }
+}
+
+
+impl Error for TransferToOccupied {}
+
+
+/// The panic payload raised when attempting to steal from a slot whose value is currently stolen
+/// by another guard, such as a `TestSlot`. Slots which cannot tell this apart from being empty
+/// raise a `StealFromNone` instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AlreadyStolen {
+    type_name: &'static str,
+    slot: usize,
+}
+
+
+impl AlreadyStolen {
+    /// The name of the type of the value which was to be stolen.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+
+    /// The address of the slot which was stolen from.
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+
+    /// Returns `true` if the given slot is the one which was stolen from.
+    pub fn is_slot<S>(&self, slot: &S) -> bool {
+        self.slot == slot as *const S as usize
+    }
+}
+
+
+impl fmt::Display for AlreadyStolen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "attempted to steal a value which is already stolen (`{}` slot at {:#x})",
+            self.type_name, self.slot
+        )
+    }
+}
+
+
+impl Error for AlreadyStolen {}
+
+
+/// The message of a panic raised by this crate, given the panic's payload, or `None` if the panic
+/// came from elsewhere. The default panic hook cannot print this crate's typed payloads, so a
+/// custom hook can use this to print them in full.
+///
+/// # Examples
+///
+/// ```
+/// # use empty_option::{violation_message, EmptyOptionExt};
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// let mut thing: Option<i32> = None;
+///
+/// let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+///     thing.steal_mut();
+/// })).unwrap_err();
+///
+/// assert!(violation_message(&*payload).unwrap().starts_with("attempted to steal from None"));
+/// assert_eq!(violation_message(&"elsewhere"), None);
+/// ```
+#[cfg(feature = "std")]
+pub fn violation_message(payload: &(dyn Any + Send)) -> Option<String> {
+    if let Some(leaked) = payload.downcast_ref::<GuardLeaked>() {
+        Some(leaked.to_string())
+    } else if let Some(from_none) = payload.downcast_ref::<StealFromNone>() {
+        Some(from_none.to_string())
+    } else if let Some(stolen) = payload.downcast_ref::<AlreadyStolen>() {
+        Some(stolen.to_string())
+    } else {
+        payload
+            .downcast_ref::<TransferToOccupied>()
+            .map(|occupied| occupied.to_string())
+    }
+}
+
+
+/// Panic with `payload`. Without `std` there are no typed payloads, so this panics with the
+/// payload's message instead.
+#[track_caller]
+pub(crate) fn raise<P: Any + Send + fmt::Display>(payload: P) -> ! {
+    #[cfg(feature = "std")]
+    panic::panic_any(payload);
+
+    #[cfg(not(feature = "std"))]
+    panic!("{}", payload);
+}
+
+
+/// Panic with a `GuardLeaked` payload for the value of type `T` which belonged in `slot`.
+#[track_caller]
+pub(crate) fn guard_leaked<T, S>(slot: &S) -> ! {
+    raise(GuardLeaked::new::<T, S>(slot))
+}
+
+
+/// Describe the leak of the value of type `T` stolen from `slot` at `location`.
+pub(crate) fn leak_of<T, S>(
+    slot: &S,
+    location: &'static Location<'static>,
+    context: Option<&'static str>,
+) -> GuardLeaked {
+    GuardLeaked {
+        location: Some(location),
+        context,
+        ..GuardLeaked::new::<T, S>(slot)
+    }
+}
+
+
+/// Print a diagnostic to standard error, and abort.
+#[cfg(feature = "std")]
+pub(crate) fn abort(diagnostic: &dyn fmt::Display) -> ! {
+    eprintln!("fatal: {}", diagnostic);
+    process::abort()
+}
+
+
+/// Panic with a diagnostic, and abort. `core` has no way to abort directly, but a panic while
+/// unwinding always aborts, so a drop guard panics a second time if the first panic unwinds.
+#[cfg(not(feature = "std"))]
+#[track_caller]
+pub(crate) fn abort(diagnostic: &dyn fmt::Display) -> ! {
+    struct PanicAgain;
+
+    impl Drop for PanicAgain {
+        fn drop(&mut self) {
+            panic!("aborting");
+        }
+    }
+
+    let _abort = PanicAgain;
+
+    panic!("fatal: {}", diagnostic)
+}
+
+
+/// Print the backtrace captured when a leaked guard was created, if one was captured.
+#[cfg(feature = "backtrace")]
+pub(crate) fn report_backtrace(backtrace: &Backtrace) {
+    if backtrace.status() == BacktraceStatus::Captured {
+        eprintln!("the leaked guard was created at:\n{}", backtrace);
+    }
+}
+
+
+/// Panic with a `StealFromNone` payload for the value of type `T` missing from `slot`.
+#[track_caller]
+pub(crate) fn steal_from_none<T, S>(slot: &S) -> ! {
+    raise(StealFromNone::new::<T, S>(slot, None))
+}
+
+
+/// Like `steal_from_none`, with the context the steal was made in.
+#[track_caller]
+pub(crate) fn steal_from_none_with<T, S>(slot: &S, context: &'static str) -> ! {
+    raise(StealFromNone::new::<T, S>(slot, Some(context)))
+}
+
+
+/// Print the `StealFromNone` diagnostic for the value of type `T` missing from `slot`, and abort.
+#[track_caller]
+pub(crate) fn steal_from_none_abort<T, S>(slot: &S) -> ! {
+    abort(&StealFromNone::new::<T, S>(slot, None))
+}
+
+
+/// Panic with a `TransferToOccupied` payload for the occupied `slot`, which was to take over the
+/// obligation for a value of type `T`.
+#[track_caller]
+pub(crate) fn transfer_to_occupied<T, S>(slot: &S, context: Option<&'static str>) -> ! {
+    raise(TransferToOccupied {
+        type_name: any::type_name::<T>(),
+        slot: slot as *const S as usize,
+        context,
+    })
+}
+
+
+/// Panic with an `AlreadyStolen` payload for the value of type `T` currently stolen from `slot`.
+#[cfg(feature = "std")]
+#[track_caller]
+pub(crate) fn already_stolen<T, S>(slot: &S) -> ! {
+    raise(AlreadyStolen {
+        type_name: any::type_name::<T>(),
+        slot: slot as *const S as usize,
+    })
+}